@@ -115,10 +115,12 @@ impl framework::Example for Example {
             vertex_stage: wgpu::ProgrammableStageDescriptor {
                 module: &vs_module,
                 entry_point: "main",
+                constants: &[],
             },
             fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
                 module: &fs_module,
                 entry_point: "main",
+                constants: &[],
             }),
             rasterization_state: Some(wgpu::RasterizationStateDescriptor {
                 front_face: wgpu::FrontFace::Ccw,
@@ -156,6 +158,7 @@ impl framework::Example for Example {
             compute_stage: wgpu::ProgrammableStageDescriptor {
                 module: &boids_module,
                 entry_point: "main",
+                constants: &[],
             },
         });
 
@@ -271,6 +274,7 @@ impl framework::Example for Example {
             },
         }];
         let render_pass_descriptor = wgpu::RenderPassDescriptor {
+            label: None,
             color_attachments: &color_attachments,
             depth_stencil_attachment: None,
         };
@@ -282,7 +286,8 @@ impl framework::Example for Example {
         command_encoder.push_debug_group("compute boid movement");
         {
             // compute pass
-            let mut cpass = command_encoder.begin_compute_pass();
+            let mut cpass =
+                command_encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: None });
             cpass.set_pipeline(&self.compute_pipeline);
             cpass.set_bind_group(0, &self.particle_bind_groups[self.frame_num % 2], &[]);
             cpass.dispatch(self.work_group_count, 1, 1);
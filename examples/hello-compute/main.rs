@@ -123,6 +123,7 @@ async fn execute_gpu(numbers: Vec<u32>) -> Vec<u32> {
         compute_stage: wgpu::ProgrammableStageDescriptor {
             module: &cs_module,
             entry_point: "main",
+            constants: &[],
         },
     });
 
@@ -131,7 +132,7 @@ async fn execute_gpu(numbers: Vec<u32>) -> Vec<u32> {
     let mut encoder =
         device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
     {
-        let mut cpass = encoder.begin_compute_pass();
+        let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: None });
         cpass.set_pipeline(&compute_pipeline);
         cpass.set_bind_group(0, &bind_group, &[]);
         cpass.insert_debug_marker("compute collatz iterations");
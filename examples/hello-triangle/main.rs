@@ -46,10 +46,12 @@ async fn run(event_loop: EventLoop<()>, window: Window, swapchain_format: wgpu::
         vertex_stage: wgpu::ProgrammableStageDescriptor {
             module: &vs_module,
             entry_point: "main",
+            constants: &[],
         },
         fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
             module: &fs_module,
             entry_point: "main",
+            constants: &[],
         }),
         // Use the default rasterizer state: no culling, no depth bias
         rasterization_state: None,
@@ -107,6 +109,7 @@ async fn run(event_loop: EventLoop<()>, window: Window, swapchain_format: wgpu::
                     device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
                 {
                     let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                        label: None,
                         color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
                             attachment: &frame.view,
                             resolve_target: None,
@@ -56,10 +56,12 @@ impl Example {
             vertex_stage: wgpu::ProgrammableStageDescriptor {
                 module: vs_module,
                 entry_point: "main",
+                constants: &[],
             },
             fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
                 module: fs_module,
                 entry_point: "main",
+                constants: &[],
             }),
             rasterization_state: Some(wgpu::RasterizationStateDescriptor {
                 front_face: wgpu::FrontFace::Ccw,
@@ -273,6 +275,7 @@ impl framework::Example for Example {
 
             encoder
                 .begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: None,
                     color_attachments: &[rpass_color_attachment],
                     depth_stencil_attachment: None,
                 })
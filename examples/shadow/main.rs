@@ -479,10 +479,12 @@ impl framework::Example for Example {
                 vertex_stage: wgpu::ProgrammableStageDescriptor {
                     module: &vs_module,
                     entry_point: "main",
+                    constants: &[],
                 },
                 fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
                     module: &fs_module,
                     entry_point: "main",
+                    constants: &[],
                 }),
                 rasterization_state: Some(wgpu::RasterizationStateDescriptor {
                     front_face: wgpu::FrontFace::Ccw,
@@ -613,10 +615,12 @@ impl framework::Example for Example {
                 vertex_stage: wgpu::ProgrammableStageDescriptor {
                     module: &vs_module,
                     entry_point: "main",
+                    constants: &[],
                 },
                 fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
                     module: &fs_module,
                     entry_point: "main",
+                    constants: &[],
                 }),
                 rasterization_state: Some(wgpu::RasterizationStateDescriptor {
                     front_face: wgpu::FrontFace::Ccw,
@@ -752,57 +756,57 @@ impl framework::Example for Example {
         let mut encoder =
             device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
 
-        encoder.push_debug_group("shadow passes");
-        for (i, light) in self.lights.iter().enumerate() {
-            encoder.push_debug_group(&format!(
-                "shadow pass {} (light at position {:?})",
-                i, light.pos
-            ));
-
-            // The light uniform buffer already has the projection,
-            // let's just copy it over to the shadow uniform buffer.
-            encoder.copy_buffer_to_buffer(
-                &self.light_uniform_buf,
-                (i * mem::size_of::<LightRaw>()) as wgpu::BufferAddress,
-                &self.shadow_pass.uniform_buf,
-                0,
-                64,
-            );
+        {
+            let mut encoder = encoder.debug_group("shadow passes");
+            for (i, light) in self.lights.iter().enumerate() {
+                let mut encoder = encoder.debug_group(&format!(
+                    "shadow pass {} (light at position {:?})",
+                    i, light.pos
+                ));
+
+                // The light uniform buffer already has the projection,
+                // let's just copy it over to the shadow uniform buffer.
+                encoder.copy_buffer_to_buffer(
+                    &self.light_uniform_buf,
+                    (i * mem::size_of::<LightRaw>()) as wgpu::BufferAddress,
+                    &self.shadow_pass.uniform_buf,
+                    0,
+                    64,
+                );
 
-            encoder.insert_debug_marker("render entities");
-            {
-                let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                    color_attachments: &[],
-                    depth_stencil_attachment: Some(
-                        wgpu::RenderPassDepthStencilAttachmentDescriptor {
-                            attachment: &light.target_view,
-                            depth_ops: Some(wgpu::Operations {
-                                load: wgpu::LoadOp::Clear(1.0),
-                                store: true,
-                            }),
-                            stencil_ops: None,
-                        },
-                    ),
-                });
-                pass.set_pipeline(&self.shadow_pass.pipeline);
-                pass.set_bind_group(0, &self.shadow_pass.bind_group, &[]);
-
-                for entity in &self.entities {
-                    pass.set_bind_group(1, &self.entity_bind_group, &[entity.uniform_offset]);
-                    pass.set_index_buffer(entity.index_buf.slice(..));
-                    pass.set_vertex_buffer(0, entity.vertex_buf.slice(..));
-                    pass.draw_indexed(0..entity.index_count as u32, 0, 0..1);
+                encoder.insert_debug_marker("render entities");
+                {
+                    let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                        label: Some("shadow pass"),
+                        color_attachments: &[],
+                        depth_stencil_attachment: Some(
+                            wgpu::RenderPassDepthStencilAttachmentDescriptor {
+                                attachment: &light.target_view,
+                                depth_ops: Some(wgpu::Operations {
+                                    load: wgpu::LoadOp::Clear(1.0),
+                                    store: true,
+                                }),
+                                stencil_ops: None,
+                            },
+                        ),
+                    });
+                    pass.set_pipeline(&self.shadow_pass.pipeline);
+                    pass.set_bind_group(0, &self.shadow_pass.bind_group, &[]);
+
+                    for entity in &self.entities {
+                        pass.set_bind_group(1, &self.entity_bind_group, &[entity.uniform_offset]);
+                        pass.set_index_buffer(entity.index_buf.slice(..));
+                        pass.set_vertex_buffer(0, entity.vertex_buf.slice(..));
+                        pass.draw_indexed(0..entity.index_count as u32, 0, 0..1);
+                    }
                 }
             }
-
-            encoder.pop_debug_group();
         }
-        encoder.pop_debug_group();
 
         // forward pass
-        encoder.push_debug_group("forward rendering pass");
         {
             let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("forward rendering pass"),
                 color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
                     attachment: &frame.view,
                     resolve_target: None,
@@ -835,7 +839,6 @@ impl framework::Example for Example {
                 pass.draw_indexed(0..entity.index_count as u32, 0, 0..1);
             }
         }
-        encoder.pop_debug_group();
 
         queue.submit(iter::once(encoder.finish()));
     }
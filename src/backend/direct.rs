@@ -8,8 +8,23 @@ use crate::{
 
 use arrayvec::ArrayVec;
 use futures::future::{ready, Ready};
+use parking_lot::Mutex;
 use smallvec::SmallVec;
-use std::{borrow::Cow::Borrowed, error::Error, fmt, marker::PhantomData, ops::Range, slice};
+use std::{
+    borrow::Cow::{self, Borrowed},
+    collections::HashMap,
+    error::Error,
+    fmt,
+    future::Future,
+    marker::PhantomData,
+    ops::{Deref, DerefMut, Range},
+    panic,
+    pin::Pin,
+    slice,
+    sync::Arc,
+    task::{self, Poll},
+    thread,
+};
 use typed_arena::Arena;
 
 pub struct Context(wgc::hub::Global<wgc::hub::IdentityManagerFactory>);
@@ -27,6 +42,142 @@ impl Context {
             }))
     }
 
+    /// Map a programmable stage, creating a specialized copy of its module if it
+    /// overrides any constants. Such copies are added to `specialized_modules`,
+    /// and have to be dropped once the pipeline is created.
+    fn map_programmable_stage(
+        &self,
+        device: &wgc::id::DeviceId,
+        stage: &crate::ProgrammableStageDescriptor,
+        specialized_modules: &mut SpecializedModules,
+    ) -> Result<wgc::pipeline::ProgrammableStageDescriptor<'static>, crate::CreatePipelineError>
+    {
+        let module = stage.module.valid_id();
+        let id = if stage.constants.is_empty() {
+            module.id
+        } else {
+            let error = |description: String| crate::CreatePipelineError { description };
+            let mut spirv = module.spirv.clone().ok_or_else(|| {
+                error(format!(
+                    "Entry point {:?} overrides constants, which is only supported for SPIR-V \
+                     shader modules created with `Device::create_specializable_shader_module`",
+                    stage.entry_point
+                ))
+            })?;
+            specialize_spirv(&mut spirv, stage.constants)
+                .map_err(|err| error(format!("Specializing shader module failed: {}", err)))?;
+            let global = &self.0;
+            let id = wgc::gfx_select!(*device => global.device_create_shader_module(
+                *device,
+                wgc::pipeline::ShaderModuleSource::SpirV(Cow::Owned(spirv)),
+                PhantomData
+            ))
+            .map_err(|err| error(format!("Specializing shader module failed: {}", err)))?;
+            specialized_modules.push(id);
+            id
+        };
+        Ok(wgc::pipeline::ProgrammableStageDescriptor {
+            module: id,
+            entry_point: Cow::Owned(stage.entry_point.to_string()),
+        })
+    }
+
+    /// Map a render pipeline descriptor into an owned one, which can be sent to
+    /// a worker thread, along with the specialized modules created for it.
+    fn map_render_pipeline_descriptor(
+        &self,
+        device: &wgc::id::DeviceId,
+        desc: &RenderPipelineDescriptor,
+    ) -> Result<
+        (
+            wgc::pipeline::RenderPipelineDescriptor<'static>,
+            SpecializedModules,
+        ),
+        crate::CreatePipelineError,
+    > {
+        use wgc::pipeline as pipe;
+
+        let mut specialized_modules = SmallVec::new();
+        let vertex_stage =
+            self.map_programmable_stage(device, &desc.vertex_stage, &mut specialized_modules);
+        let fragment_stage = match vertex_stage {
+            Ok(_) => desc
+                .fragment_stage
+                .as_ref()
+                .map(|fs| self.map_programmable_stage(device, fs, &mut specialized_modules))
+                .transpose(),
+            Err(_) => Ok(None),
+        };
+        let (vertex_stage, fragment_stage) = match (vertex_stage, fragment_stage) {
+            (Ok(vertex_stage), Ok(fragment_stage)) => (vertex_stage, fragment_stage),
+            (Err(err), _) | (_, Err(err)) => {
+                self.drop_specialized_modules(specialized_modules);
+                return Err(err);
+            }
+        };
+        let vertex_buffers = desc
+            .vertex_state
+            .vertex_buffers
+            .iter()
+            .map(|vertex_buffer| pipe::VertexBufferDescriptor {
+                stride: vertex_buffer.stride,
+                step_mode: vertex_buffer.step_mode,
+                attributes: Cow::Owned(vertex_buffer.attributes.to_vec()),
+            })
+            .collect::<Vec<_>>();
+        let vertex_state = pipe::VertexStateDescriptor {
+            index_format: desc.vertex_state.index_format,
+            vertex_buffers: Cow::Owned(vertex_buffers),
+        };
+
+        let desc = pipe::RenderPipelineDescriptor {
+            label: desc.label.map(|label| Cow::Owned(label.to_string())),
+            layout: desc.layout.map(|l| l.owner.id),
+            vertex_stage,
+            fragment_stage,
+            rasterization_state: desc.rasterization_state.clone(),
+            primitive_topology: desc.primitive_topology,
+            color_states: Cow::Owned(desc.color_states.to_vec()),
+            depth_stencil_state: desc.depth_stencil_state.clone(),
+            vertex_state,
+            sample_count: desc.sample_count,
+            sample_mask: desc.sample_mask,
+            alpha_to_coverage_enabled: desc.alpha_to_coverage_enabled,
+        };
+        Ok((desc, specialized_modules))
+    }
+
+    /// Map a compute pipeline descriptor into an owned one, which can be sent to
+    /// a worker thread, along with the specialized modules created for it.
+    fn map_compute_pipeline_descriptor(
+        &self,
+        device: &wgc::id::DeviceId,
+        desc: &ComputePipelineDescriptor,
+    ) -> Result<
+        (
+            wgc::pipeline::ComputePipelineDescriptor<'static>,
+            SpecializedModules,
+        ),
+        crate::CreatePipelineError,
+    > {
+        let mut specialized_modules = SmallVec::new();
+        let compute_stage =
+            self.map_programmable_stage(device, &desc.compute_stage, &mut specialized_modules)?;
+        let desc = wgc::pipeline::ComputePipelineDescriptor {
+            label: desc.label.map(|label| Cow::Owned(label.to_string())),
+            layout: desc.layout.map(|l| l.owner.id),
+            compute_stage,
+        };
+        Ok((desc, specialized_modules))
+    }
+
+    fn drop_specialized_modules(&self, specialized_modules: SpecializedModules) {
+        let global = &self.0;
+        for id in specialized_modules {
+            wgc::gfx_select!(id => global.shader_module_drop(id));
+        }
+    }
+
     #[cfg(any(target_os = "ios", target_os = "macos"))]
     pub unsafe fn create_surface_from_core_animation_layer(
         &self,
@@ -57,6 +208,30 @@ impl fmt::Debug for Context {
     }
 }
 
+/// Pass being recorded, remembering whether it has a label.
+///
+/// wgpu-core passes have no label, so it is pushed as a debug group of the command encoder
+/// around the pass instead, which shows up the same way in graphics debuggers.
+#[derive(Debug)]
+pub(crate) struct Pass<P> {
+    pass: P,
+    labeled: bool,
+}
+
+impl<P> Deref for Pass<P> {
+    type Target = P;
+
+    fn deref(&self) -> &P {
+        &self.pass
+    }
+}
+
+impl<P> DerefMut for Pass<P> {
+    fn deref_mut(&mut self) -> &mut P {
+        &mut self.pass
+    }
+}
+
 mod pass_impl {
     use super::Context;
     use smallvec::SmallVec;
@@ -64,7 +239,7 @@ mod pass_impl {
     use std::ops::Range;
     use wgc::command::{bundle_ffi::*, compute_ffi::*, render_ffi::*};
 
-    impl crate::ComputePassInner<Context> for wgc::command::ComputePass {
+    impl crate::ComputePassInner<Context> for super::Pass<wgc::command::ComputePass> {
         fn set_pipeline(&mut self, pipeline: &wgc::id::ComputePipelineId) {
             wgpu_compute_pass_set_pipeline(self, *pipeline)
         }
@@ -124,7 +299,7 @@ mod pass_impl {
         }
     }
 
-    impl crate::RenderInner<Context> for wgc::command::RenderPass {
+    impl crate::RenderInner<Context> for super::Pass<wgc::command::RenderPass> {
         fn set_pipeline(&mut self, pipeline: &wgc::id::RenderPipelineId) {
             wgpu_render_pass_set_pipeline(self, *pipeline)
         }
@@ -264,7 +439,7 @@ mod pass_impl {
         }
     }
 
-    impl crate::RenderPassInner<Context> for wgc::command::RenderPass {
+    impl crate::RenderPassInner<Context> for super::Pass<wgc::command::RenderPass> {
         fn set_blend_color(&mut self, color: wgt::Color) {
             wgpu_render_pass_set_blend_color(self, &color)
         }
@@ -445,14 +620,14 @@ mod pass_impl {
 
 fn map_buffer_copy_view(view: crate::BufferCopyView) -> wgc::command::BufferCopyView {
     wgc::command::BufferCopyView {
-        buffer: view.buffer.id,
+        buffer: *view.buffer.valid_id(),
         layout: view.layout,
     }
 }
 
 fn map_texture_copy_view(view: crate::TextureCopyView) -> wgc::command::TextureCopyView {
     wgc::command::TextureCopyView {
-        texture: view.texture.id,
+        texture: *view.texture.valid_id(),
         mip_level: view.mip_level,
         origin: view.origin,
     }
@@ -497,11 +672,74 @@ fn map_pass_channel<V: Copy + Default>(
     }
 }
 
+type FragmentOutputs = Vec<(String, Vec<crate::FragmentOutput>)>;
+
+/// Reflect the fragment outputs of a SPIR-V module.
+///
+/// Naga doesn't support all of SPIR-V yet, so reflection is best effort.
+#[cfg(feature = "naga")]
+fn reflect_spirv(spv: &[u32]) -> Option<FragmentOutputs> {
+    naga::front::spv::Parser::new(spv.iter().cloned())
+        .parse()
+        .ok()
+        .map(|module| collect_fragment_outputs(&module))
+}
+
+#[cfg(not(feature = "naga"))]
+fn reflect_spirv(_spv: &[u32]) -> Option<FragmentOutputs> {
+    None
+}
+
+/// Parse WGSL code and reflect its fragment outputs.
+///
+/// wgpu-core unwraps WGSL parsing errors, so the code is parsed here first
+/// in order to report them instead of panicking. This needs naga, which
+/// is only a dependency with the `wgsl-validation` feature.
+#[cfg(feature = "wgsl-validation")]
+fn parse_wgsl(code: &str) -> Result<Option<FragmentOutputs>, crate::CompilationMessage> {
+    match naga::front::wgsl::parse_str(code) {
+        Ok(module) => Ok(Some(collect_fragment_outputs(&module))),
+        Err(err) => Err(map_wgsl_error(code, err)),
+    }
+}
+
+#[cfg(not(feature = "wgsl-validation"))]
+fn parse_wgsl(_code: &str) -> Result<Option<FragmentOutputs>, crate::CompilationMessage> {
+    Ok(None)
+}
+
+#[cfg(feature = "wgsl-validation")]
+fn map_wgsl_error(code: &str, err: naga::front::wgsl::ParseError) -> crate::CompilationMessage {
+    // Naga reports the line, and the length of that line up to where parsing stopped.
+    let (line, column) = err.pos;
+    let line_start = code
+        .split_terminator('\n')
+        .take(line.saturating_sub(1))
+        .map(|line| line.len() + 1)
+        .sum::<usize>()
+        .min(code.len());
+    let mut offset = (line_start + column).min(code.len());
+    while !code.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let length = code[offset..]
+        .find(char::is_whitespace)
+        .unwrap_or_else(|| code.len() - offset);
+    crate::CompilationMessage {
+        message: err.error.to_string(),
+        message_type: crate::CompilationMessageType::Error,
+        line_number: line.max(1) as u64,
+        line_position: (offset - line_start + 1) as u64,
+        offset: offset as u64,
+        length: length as u64,
+    }
+}
+
 impl crate::Context for Context {
     type AdapterId = wgc::id::AdapterId;
     type DeviceId = wgc::id::DeviceId;
     type QueueId = wgc::id::QueueId;
-    type ShaderModuleId = wgc::id::ShaderModuleId;
+    type ShaderModuleId = ShaderModule;
     type BindGroupLayoutId = wgc::id::BindGroupLayoutId;
     type BindGroupId = wgc::id::BindGroupId;
     type TextureViewId = wgc::id::TextureViewId;
@@ -512,8 +750,8 @@ impl crate::Context for Context {
     type RenderPipelineId = wgc::id::RenderPipelineId;
     type ComputePipelineId = wgc::id::ComputePipelineId;
     type CommandEncoderId = wgc::id::CommandEncoderId;
-    type ComputePassId = wgc::command::ComputePass;
-    type RenderPassId = wgc::command::RenderPass;
+    type ComputePassId = Pass<wgc::command::ComputePass>;
+    type RenderPassId = Pass<wgc::command::RenderPass>;
     type CommandBufferId = wgc::id::CommandBufferId;
     type RenderBundleEncoderId = wgc::command::RenderBundleEncoder;
     type RenderBundleId = wgc::id::RenderBundleId;
@@ -526,6 +764,9 @@ impl crate::Context for Context {
     type RequestDeviceFuture =
         Ready<Result<(Self::DeviceId, Self::QueueId), crate::RequestDeviceError>>;
     type MapAsyncFuture = native_gpu_future::GpuFuture<Result<(), crate::BufferAsyncError>>;
+    type CreateRenderPipelineFuture = PipelineFuture<Self::RenderPipelineId>;
+    type CreateComputePipelineFuture = PipelineFuture<Self::ComputePipelineId>;
+    type CompilationInfoFuture = Ready<crate::CompilationInfo>;
 
     fn init(backends: wgt::BackendBit) -> Self {
         Self(wgc::hub::Global::new(
@@ -607,16 +848,44 @@ impl crate::Context for Context {
         &self,
         device: &Self::DeviceId,
         source: ShaderModuleSource,
-    ) -> Self::ShaderModuleId {
+        specializable: bool,
+    ) -> Result<Self::ShaderModuleId, crate::CreateShaderModuleError> {
+        let mut spirv = None;
+        let fragment_outputs;
         let desc = match source {
-            ShaderModuleSource::SpirV(spv) => wgc::pipeline::ShaderModuleSource::SpirV(spv),
-            ShaderModuleSource::Wgsl(code) => wgc::pipeline::ShaderModuleSource::Wgsl(code),
+            ShaderModuleSource::SpirV(spv) => {
+                // Reflection is deferred to the first use of the outputs.
+                fragment_outputs = None;
+                if specializable {
+                    spirv = Some(spv.to_vec());
+                }
+                wgc::pipeline::ShaderModuleSource::SpirV(spv)
+            }
+            ShaderModuleSource::Wgsl(code) => {
+                fragment_outputs =
+                    Some(parse_wgsl(&code).map_err(crate::CreateShaderModuleError::new)?);
+                wgc::pipeline::ShaderModuleSource::Wgsl(code)
+            }
         };
         let global = &self.0;
         wgc::gfx_select!(
             *device => global.device_create_shader_module(*device, desc, PhantomData)
         )
-        .unwrap_pretty()
+        .map(|id| ShaderModule {
+            id,
+            spirv,
+            fragment_outputs: Mutex::new(fragment_outputs),
+        })
+        .map_err(|err| {
+            crate::CreateShaderModuleError::new(crate::CompilationMessage {
+                message: err.to_string(),
+                message_type: crate::CompilationMessageType::Error,
+                line_number: 0,
+                line_position: 0,
+                offset: 0,
+                length: 0,
+            })
+        })
     }
 
     fn device_create_bind_group_layout(
@@ -654,18 +923,21 @@ impl crate::Context for Context {
                         offset,
                         size,
                     } => bm::BindingResource::Buffer(bm::BufferBinding {
-                        buffer_id: buffer.id,
+                        buffer_id: *buffer.valid_id(),
                         offset,
                         size,
                     }),
-                    BindingResource::Sampler(sampler) => bm::BindingResource::Sampler(sampler.id),
+                    BindingResource::Sampler(sampler) => {
+                        bm::BindingResource::Sampler(sampler.owner.id)
+                    }
                     BindingResource::TextureView(texture_view) => {
-                        bm::BindingResource::TextureView(texture_view.id)
+                        bm::BindingResource::TextureView(*texture_view.valid_id())
                     }
                     BindingResource::TextureViewArray(texture_view_array) => {
                         bm::BindingResource::TextureViewArray(Borrowed(
-                            texture_view_arena
-                                .alloc_extend(texture_view_array.iter().map(|view| view.id)),
+                            texture_view_arena.alloc_extend(
+                                texture_view_array.iter().map(|view| *view.valid_id()),
+                            ),
                         ))
                     }
                 },
@@ -677,7 +949,7 @@ impl crate::Context for Context {
             *device,
             &bm::BindGroupDescriptor {
                 label: desc.label.as_ref().map(|label| Borrowed(&label[..])),
-                layout: desc.layout.id,
+                layout: desc.layout.owner.id,
                 entries: Borrowed(&entries),
             },
             PhantomData
@@ -704,7 +976,7 @@ impl crate::Context for Context {
         let temp_layouts = desc
             .bind_group_layouts
             .iter()
-            .map(|bgl| bgl.id)
+            .map(|bgl| bgl.owner.id)
             .collect::<ArrayVec<[_; wgc::MAX_BIND_GROUPS]>>();
 
         let global = &self.0;
@@ -724,83 +996,111 @@ impl crate::Context for Context {
         &self,
         device: &Self::DeviceId,
         desc: &RenderPipelineDescriptor,
-    ) -> Self::RenderPipelineId {
+    ) -> Result<Self::RenderPipelineId, crate::CreatePipelineError> {
         wgc::span!(_guard, TRACE, "Device::create_render_pipeline wrapper");
-        use wgc::pipeline as pipe;
 
-        let vertex_stage = pipe::ProgrammableStageDescriptor {
-            module: desc.vertex_stage.module.id,
-            entry_point: Borrowed(&desc.vertex_stage.entry_point),
-        };
-        let fragment_stage =
-            desc.fragment_stage
-                .as_ref()
-                .map(|fs| pipe::ProgrammableStageDescriptor {
-                    module: fs.module.id,
-                    entry_point: Borrowed(&fs.entry_point),
-                });
-        let vertex_buffers: ArrayVec<[_; wgc::device::MAX_VERTEX_BUFFERS]> = desc
-            .vertex_state
-            .vertex_buffers
-            .iter()
-            .map(|vertex_buffer| pipe::VertexBufferDescriptor {
-                stride: vertex_buffer.stride,
-                step_mode: vertex_buffer.step_mode,
-                attributes: Borrowed(vertex_buffer.attributes),
-            })
-            .collect();
-        let vertex_state = pipe::VertexStateDescriptor {
-            index_format: desc.vertex_state.index_format,
-            vertex_buffers: Borrowed(&vertex_buffers),
-        };
+        let (desc, specialized_modules) = self.map_render_pipeline_descriptor(device, desc)?;
 
         let global = &self.0;
-        wgc::gfx_select!(*device => global.device_create_render_pipeline(
+        let result = wgc::gfx_select!(*device => global.device_create_render_pipeline(
             *device,
-            &pipe::RenderPipelineDescriptor {
-                label: desc.label.map(Borrowed),
-                layout: desc.layout.map(|l| l.id),
-                vertex_stage,
-                fragment_stage,
-                rasterization_state: desc.rasterization_state.clone(),
-                primitive_topology: desc.primitive_topology,
-                color_states: Borrowed(&desc.color_states),
-                depth_stencil_state: desc.depth_stencil_state.clone(),
-                vertex_state: vertex_state,
-                sample_count: desc.sample_count,
-                sample_mask: desc.sample_mask,
-                alpha_to_coverage_enabled: desc.alpha_to_coverage_enabled,
-            },
+            &desc,
             PhantomData,
             None
-        ))
-        .unwrap_pretty()
-        .0
+        ));
+        self.drop_specialized_modules(specialized_modules);
+        result
+            .map(|(id, _)| id)
+            .map_err(|err| crate::CreatePipelineError {
+                description: err.to_string(),
+            })
+    }
+
+    fn device_create_render_pipeline_async(
+        self: Arc<Self>,
+        device: &Self::DeviceId,
+        desc: &RenderPipelineDescriptor,
+    ) -> Self::CreateRenderPipelineFuture {
+        let resources = PipelineResources::new(
+            desc.layout,
+            Some(&desc.vertex_stage)
+                .into_iter()
+                .chain(desc.fragment_stage.as_ref()),
+        );
+        let (desc, specialized_modules) = match self.map_render_pipeline_descriptor(device, desc) {
+            Ok(mapped) => mapped,
+            Err(err) => return PipelineFuture::failed(err),
+        };
+
+        let device = *device;
+        PipelineFuture::spawn(self, Self::render_pipeline_drop, move |context| {
+            let global = &context.0;
+            let result = wgc::gfx_select!(device => global.device_create_render_pipeline(
+                device,
+                &desc,
+                PhantomData,
+                None
+            ));
+            context.drop_specialized_modules(specialized_modules);
+            drop(resources);
+            result
+                .map(|(id, _)| id)
+                .map_err(|err| crate::CreatePipelineError {
+                    description: err.to_string(),
+                })
+        })
     }
 
     fn device_create_compute_pipeline(
         &self,
         device: &Self::DeviceId,
         desc: &ComputePipelineDescriptor,
-    ) -> Self::ComputePipelineId {
-        use wgc::pipeline as pipe;
+    ) -> Result<Self::ComputePipelineId, crate::CreatePipelineError> {
+        let (desc, specialized_modules) = self.map_compute_pipeline_descriptor(device, desc)?;
 
         let global = &self.0;
-        wgc::gfx_select!(*device => global.device_create_compute_pipeline(
+        let result = wgc::gfx_select!(*device => global.device_create_compute_pipeline(
             *device,
-            &pipe::ComputePipelineDescriptor {
-                label: desc.label.map(Borrowed),
-                layout: desc.layout.map(|l| l.id),
-                compute_stage: pipe::ProgrammableStageDescriptor {
-                    module: desc.compute_stage.module.id,
-                    entry_point: Borrowed(&desc.compute_stage.entry_point),
-                },
-            },
+            &desc,
             PhantomData,
             None
-        ))
-        .unwrap_pretty()
-        .0
+        ));
+        self.drop_specialized_modules(specialized_modules);
+        result
+            .map(|(id, _)| id)
+            .map_err(|err| crate::CreatePipelineError {
+                description: err.to_string(),
+            })
+    }
+
+    fn device_create_compute_pipeline_async(
+        self: Arc<Self>,
+        device: &Self::DeviceId,
+        desc: &ComputePipelineDescriptor,
+    ) -> Self::CreateComputePipelineFuture {
+        let resources = PipelineResources::new(desc.layout, Some(&desc.compute_stage));
+        let (desc, specialized_modules) = match self.map_compute_pipeline_descriptor(device, desc) {
+            Ok(mapped) => mapped,
+            Err(err) => return PipelineFuture::failed(err),
+        };
+
+        let device = *device;
+        PipelineFuture::spawn(self, Self::compute_pipeline_drop, move |context| {
+            let global = &context.0;
+            let result = wgc::gfx_select!(device => global.device_create_compute_pipeline(
+                device,
+                &desc,
+                PhantomData,
+                None
+            ));
+            context.drop_specialized_modules(specialized_modules);
+            drop(resources);
+            result
+                .map(|(id, _)| id)
+                .map_err(|err| crate::CreatePipelineError {
+                    description: err.to_string(),
+                })
+        })
     }
 
     fn device_create_buffer(
@@ -1055,6 +1355,11 @@ impl crate::Context for Context {
         .unwrap_pretty()
     }
 
+    fn texture_destroy(&self, texture: &Self::TextureId) {
+        // wgpu-core has no explicit destruction, but dropping frees the memory
+        // once the texture isn't used by the GPU or by any view anymore.
+        self.texture_drop(texture)
+    }
     fn texture_drop(&self, texture: &Self::TextureId) {
         let global = &self.0;
         wgc::gfx_select!(*texture => global.texture_drop(*texture))
@@ -1067,6 +1372,11 @@ impl crate::Context for Context {
         let global = &self.0;
         wgc::gfx_select!(*sampler => global.sampler_drop(*sampler))
     }
+    fn buffer_destroy(&self, buffer: &Self::BufferId) {
+        // wgpu-core has no explicit destruction, but dropping frees the memory
+        // once the buffer isn't used by the GPU anymore.
+        self.buffer_drop(buffer)
+    }
     fn buffer_drop(&self, buffer: &Self::BufferId) {
         let global = &self.0;
         wgc::gfx_select!(*buffer => global.buffer_drop(*buffer, false))
@@ -1083,9 +1393,31 @@ impl crate::Context for Context {
         let global = &self.0;
         wgc::gfx_select!(*pipeline_layout => global.pipeline_layout_drop(*pipeline_layout))
     }
+    fn shader_module_fragment_outputs(
+        &self,
+        shader_module: &Self::ShaderModuleId,
+        entry_point: &str,
+    ) -> Option<Vec<crate::FragmentOutput>> {
+        let mut fragment_outputs = shader_module.fragment_outputs.lock();
+        fragment_outputs
+            .get_or_insert_with(|| shader_module.spirv.as_deref().and_then(reflect_spirv))
+            .as_ref()?
+            .iter()
+            .find(|&&(ref name, _)| name.as_str() == entry_point)
+            .map(|&(_, ref outputs)| outputs.clone())
+    }
+
+    fn shader_module_compilation_info(
+        &self,
+        _shader_module: &Self::ShaderModuleId,
+    ) -> Self::CompilationInfoFuture {
+        // Errors are returned when creating the module, and there are no warnings.
+        ready(crate::CompilationInfo::default())
+    }
+
     fn shader_module_drop(&self, shader_module: &Self::ShaderModuleId) {
         let global = &self.0;
-        wgc::gfx_select!(*shader_module => global.shader_module_drop(*shader_module))
+        wgc::gfx_select!(shader_module.id => global.shader_module_drop(shader_module.id))
     }
     fn command_buffer_drop(&self, command_buffer: &Self::CommandBufferId) {
         let global = &self.0;
@@ -1196,8 +1528,15 @@ impl crate::Context for Context {
     fn command_encoder_begin_compute_pass(
         &self,
         encoder: &Self::CommandEncoderId,
+        desc: &crate::ComputePassDescriptor,
     ) -> Self::ComputePassId {
-        wgc::command::ComputePass::new(*encoder)
+        if let Some(label) = desc.label {
+            self.command_encoder_push_debug_group(encoder, label);
+        }
+        Pass {
+            pass: wgc::command::ComputePass::new(*encoder),
+            labeled: desc.label.is_some(),
+        }
     }
 
     fn command_encoder_end_compute_pass(
@@ -1207,9 +1546,12 @@ impl crate::Context for Context {
     ) {
         let global = &self.0;
         wgc::gfx_select!(
-            *encoder => global.command_encoder_run_compute_pass(*encoder, pass)
+            *encoder => global.command_encoder_run_compute_pass(*encoder, &pass.pass)
         )
-        .unwrap_pretty()
+        .unwrap_pretty();
+        if pass.labeled {
+            self.command_encoder_pop_debug_group(encoder);
+        }
     }
 
     fn command_encoder_begin_render_pass<'a>(
@@ -1222,27 +1564,33 @@ impl crate::Context for Context {
             .color_attachments
             .iter()
             .map(|ca| wgc::command::ColorAttachmentDescriptor {
-                attachment: ca.attachment.id,
-                resolve_target: ca.resolve_target.map(|rt| rt.id),
+                attachment: *ca.attachment.valid_id(),
+                resolve_target: ca.resolve_target.map(|rt| *rt.valid_id()),
                 channel: map_pass_channel(Some(&ca.ops)),
             })
             .collect::<ArrayVec<[_; wgc::device::MAX_COLOR_TARGETS]>>();
 
         let depth_stencil = desc.depth_stencil_attachment.as_ref().map(|dsa| {
             wgc::command::DepthStencilAttachmentDescriptor {
-                attachment: dsa.attachment.id,
+                attachment: *dsa.attachment.valid_id(),
                 depth: map_pass_channel(dsa.depth_ops.as_ref()),
                 stencil: map_pass_channel(dsa.stencil_ops.as_ref()),
             }
         });
 
-        wgc::command::RenderPass::new(
-            *encoder,
-            wgc::command::RenderPassDescriptor {
-                color_attachments: Borrowed(&colors),
-                depth_stencil_attachment: depth_stencil.as_ref(),
-            },
-        )
+        if let Some(label) = desc.label {
+            self.command_encoder_push_debug_group(encoder, label);
+        }
+        Pass {
+            pass: wgc::command::RenderPass::new(
+                *encoder,
+                wgc::command::RenderPassDescriptor {
+                    color_attachments: Borrowed(&colors),
+                    depth_stencil_attachment: depth_stencil.as_ref(),
+                },
+            ),
+            labeled: desc.label.is_some(),
+        }
     }
 
     fn command_encoder_end_render_pass(
@@ -1251,8 +1599,11 @@ impl crate::Context for Context {
         pass: &mut Self::RenderPassId,
     ) {
         let global = &self.0;
-        wgc::gfx_select!(*encoder => global.command_encoder_run_render_pass(*encoder, pass))
-            .unwrap_pretty()
+        wgc::gfx_select!(*encoder => global.command_encoder_run_render_pass(*encoder, &pass.pass))
+            .unwrap_pretty();
+        if pass.labeled {
+            self.command_encoder_pop_debug_group(encoder);
+        }
     }
 
     fn command_encoder_finish(&self, encoder: &Self::CommandEncoderId) -> Self::CommandBufferId {
@@ -1339,6 +1690,275 @@ impl crate::Context for Context {
     }
 }
 
+type SpecializedModules = SmallVec<[wgc::id::ShaderModuleId; 2]>;
+
+/// Handles to the layout and shader modules of a pipeline descriptor, keeping them
+/// alive while the pipeline is created on a worker thread.
+struct PipelineResources {
+    _layout: Option<crate::PipelineLayout>,
+    _modules: SmallVec<[crate::ShaderModule; 2]>,
+}
+
+impl PipelineResources {
+    fn new<'a>(
+        layout: Option<&crate::PipelineLayout>,
+        stages: impl Iterator<Item = &'a crate::ProgrammableStageDescriptor<'a>>,
+    ) -> Self {
+        PipelineResources {
+            _layout: layout.cloned(),
+            _modules: stages.map(|stage| stage.module.clone()).collect(),
+        }
+    }
+}
+
+/// Pipeline created by a worker thread, which is dropped again unless
+/// its future takes it.
+struct CreatedPipeline<T> {
+    context: Arc<Context>,
+    id: Option<T>,
+    drop_pipeline: fn(&Context, &T),
+}
+
+impl<T> CreatedPipeline<T> {
+    fn take(mut self) -> T {
+        self.id.take().unwrap()
+    }
+}
+
+impl<T> Drop for CreatedPipeline<T> {
+    fn drop(&mut self) {
+        if let Some(ref id) = self.id {
+            (self.drop_pipeline)(&self.context, id);
+        }
+    }
+}
+
+type PipelineResult<T> = Result<CreatedPipeline<T>, crate::CreatePipelineError>;
+
+/// Future of a pipeline being created on a worker thread.
+///
+/// The worker is detached: if the future is dropped before it completes,
+/// the pipeline is dropped along with the result.
+pub(crate) struct PipelineFuture<T> {
+    future: native_gpu_future::GpuFuture<thread::Result<PipelineResult<T>>>,
+}
+
+impl<T: Send + 'static> PipelineFuture<T> {
+    fn spawn(
+        context: Arc<Context>,
+        drop_pipeline: fn(&Context, &T),
+        create: impl FnOnce(&Context) -> Result<T, crate::CreatePipelineError> + Send + 'static,
+    ) -> Self {
+        let (future, completion) = native_gpu_future::new_gpu_future();
+        thread::spawn(move || {
+            let result = panic::catch_unwind(panic::AssertUnwindSafe(|| create(&context)));
+            completion.complete(result.map(|result| {
+                result.map(|id| CreatedPipeline {
+                    context,
+                    id: Some(id),
+                    drop_pipeline,
+                })
+            }));
+        });
+        PipelineFuture { future }
+    }
+
+    fn failed(err: crate::CreatePipelineError) -> Self {
+        let (future, completion) = native_gpu_future::new_gpu_future();
+        completion.complete(Ok(Err(err)));
+        PipelineFuture { future }
+    }
+}
+
+impl<T> Future for PipelineFuture<T> {
+    type Output = Result<T, crate::CreatePipelineError>;
+
+    fn poll(mut self: Pin<&mut Self>, context: &mut task::Context) -> Poll<Self::Output> {
+        Pin::new(&mut self.future)
+            .poll(context)
+            .map(|result| match result {
+                Ok(result) => result.map(CreatedPipeline::take),
+                Err(payload) => panic::resume_unwind(payload),
+            })
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct ShaderModule {
+    id: wgc::id::ShaderModuleId,
+    /// SPIR-V code of the module, kept for creating specialized copies if the module
+    /// was created with `Device::create_specializable_shader_module`.
+    spirv: Option<Vec<u32>>,
+    /// Outputs of every fragment entry point, if the module could be reflected.
+    ///
+    /// SPIR-V modules are only reflected on first use, which needs their code to be kept.
+    fragment_outputs: Mutex<Option<Option<FragmentOutputs>>>,
+}
+
+#[cfg(feature = "naga")]
+fn collect_fragment_outputs(module: &naga::Module) -> FragmentOutputs {
+    module
+        .entry_points
+        .iter()
+        .filter(|entry_point| entry_point.stage == naga::ShaderStage::Fragment)
+        .map(|entry_point| {
+            let function = &module.functions[entry_point.function];
+            let outputs = module
+                .global_variables
+                .iter()
+                .filter_map(|(handle, var)| {
+                    let used = function
+                        .global_usage
+                        .get(handle.index())
+                        .map_or(true, |usage| !usage.is_empty());
+                    let location = match (var.class, var.binding.as_ref()) {
+                        (naga::StorageClass::Output, Some(&naga::Binding::Location(location)))
+                            if used =>
+                        {
+                            location
+                        }
+                        _ => return None,
+                    };
+                    let kind = match module.types[var.ty].inner {
+                        naga::TypeInner::Scalar { kind, .. }
+                        | naga::TypeInner::Vector { kind, .. } => kind,
+                        _ => return None,
+                    };
+                    let component_type = match kind {
+                        naga::ScalarKind::Float => wgt::TextureComponentType::Float,
+                        naga::ScalarKind::Sint => wgt::TextureComponentType::Sint,
+                        naga::ScalarKind::Uint => wgt::TextureComponentType::Uint,
+                        naga::ScalarKind::Bool => return None,
+                    };
+                    Some(crate::FragmentOutput {
+                        location,
+                        component_type,
+                    })
+                })
+                .collect();
+            (entry_point.name.clone(), outputs)
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy)]
+enum SpecConstantType {
+    Int { width: u32, signed: bool },
+    Float { width: u32 },
+}
+
+/// Override the default values of specialization constants in a SPIR-V module.
+///
+/// Keys of `constants` are the `SpecId` decorations of the constants to override.
+fn specialize_spirv(words: &mut [u32], constants: &[(&str, f64)]) -> Result<(), String> {
+    const HEADER_LENGTH: usize = 5;
+    const OP_TYPE_INT: u32 = 21;
+    const OP_TYPE_FLOAT: u32 = 22;
+    const OP_SPEC_CONSTANT_TRUE: u32 = 48;
+    const OP_SPEC_CONSTANT_FALSE: u32 = 49;
+    const OP_SPEC_CONSTANT: u32 = 50;
+    const OP_DECORATE: u32 = 71;
+    const DECORATION_SPEC_ID: u32 = 1;
+
+    let mut values = constants
+        .iter()
+        .map(|&(key, value)| match key.parse::<u32>() {
+            Ok(spec_id) => Ok((spec_id, value, false)),
+            Err(_) => Err(format!("\"{}\" is not a specialization constant ID", key)),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut spec_ids = HashMap::new();
+    let mut types = HashMap::new();
+
+    let mut index = HEADER_LENGTH;
+    while index < words.len() {
+        let word_count = (words[index] >> 16) as usize;
+        let opcode = words[index] & 0xffff;
+        if word_count == 0 || index + word_count > words.len() {
+            return Err("SPIR-V instruction stream is malformed".to_string());
+        }
+        let instruction = &mut words[index..index + word_count];
+        let value = match opcode {
+            OP_SPEC_CONSTANT_TRUE | OP_SPEC_CONSTANT_FALSE | OP_SPEC_CONSTANT
+                if word_count >= 3 =>
+            {
+                spec_ids.get(&instruction[2]).and_then(|spec_id| {
+                    values
+                        .iter_mut()
+                        .find(|&&mut (id, _, _)| id == *spec_id)
+                        .map(|&mut (_, value, ref mut used)| {
+                            *used = true;
+                            value
+                        })
+                })
+            }
+            _ => None,
+        };
+        match opcode {
+            OP_DECORATE if word_count >= 4 && instruction[2] == DECORATION_SPEC_ID => {
+                spec_ids.insert(instruction[1], instruction[3]);
+            }
+            OP_TYPE_INT if word_count >= 4 => {
+                let ty = SpecConstantType::Int {
+                    width: instruction[2],
+                    signed: instruction[3] != 0,
+                };
+                types.insert(instruction[1], ty);
+            }
+            OP_TYPE_FLOAT if word_count >= 3 => {
+                let ty = SpecConstantType::Float {
+                    width: instruction[2],
+                };
+                types.insert(instruction[1], ty);
+            }
+            OP_SPEC_CONSTANT_TRUE | OP_SPEC_CONSTANT_FALSE => {
+                if let Some(value) = value {
+                    let opcode = if value != 0.0 {
+                        OP_SPEC_CONSTANT_TRUE
+                    } else {
+                        OP_SPEC_CONSTANT_FALSE
+                    };
+                    instruction[0] = (word_count as u32) << 16 | opcode;
+                }
+            }
+            OP_SPEC_CONSTANT => {
+                if let Some(value) = value {
+                    let bits = match types.get(&instruction[1]) {
+                        Some(&SpecConstantType::Float { width: 32 }) => {
+                            (value as f32).to_bits() as u64
+                        }
+                        Some(&SpecConstantType::Float { width: 64 }) => value.to_bits(),
+                        Some(&SpecConstantType::Int {
+                            width,
+                            signed: true,
+                        }) if width <= 64 => value as i64 as u64,
+                        Some(&SpecConstantType::Int {
+                            width,
+                            signed: false,
+                        }) if width <= 64 => value as u64,
+                        _ => {
+                            return Err(format!(
+                                "specialization constant with ID {} has an unsupported type",
+                                spec_ids[&instruction[2]]
+                            ))
+                        }
+                    };
+                    for (i, word) in instruction[3..].iter_mut().enumerate().take(2) {
+                        *word = (bits >> (32 * i)) as u32;
+                    }
+                }
+            }
+            _ => {}
+        }
+        index += word_count;
+    }
+
+    match values.iter().find(|&&(_, _, used)| !used) {
+        Some(&(spec_id, _, _)) => Err(format!("no specialization constant with ID {}", spec_id)),
+        None => Ok(()),
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct SwapChainOutputDetail {
     swap_chain_id: wgc::id::SwapChainId,
@@ -13,9 +13,10 @@ use std::{
     marker::PhantomData,
     ops::Range,
     pin::Pin,
+    sync::Arc,
     task::{self, Poll},
 };
-use wasm_bindgen::prelude::*;
+use wasm_bindgen::{prelude::*, JsCast};
 
 // We need to make a wrapper for some of the handle types returned by the web backend to make them
 // implement `Send` and `Sync` to match native.
@@ -340,7 +341,22 @@ fn map_texture_component_type(
 fn map_stage_descriptor(
     desc: &ProgrammableStageDescriptor,
 ) -> web_sys::GpuProgrammableStageDescriptor {
-    web_sys::GpuProgrammableStageDescriptor::new(&desc.entry_point, &desc.module.id.0)
+    let mapped =
+        web_sys::GpuProgrammableStageDescriptor::new(&desc.entry_point, &desc.module.valid_id().0);
+    if !desc.constants.is_empty() {
+        // `web_sys` doesn't expose `constants` yet, so set it on the dictionary directly.
+        let constants = js_sys::Object::new();
+        for &(key, value) in desc.constants {
+            js_sys::Reflect::set(
+                &constants,
+                &JsValue::from_str(key),
+                &JsValue::from_f64(value),
+            )
+            .unwrap();
+        }
+        js_sys::Reflect::set(&mapped, &JsValue::from_str("constants"), &constants).unwrap();
+    }
+    mapped
 }
 
 fn map_cull_mode(cull_mode: wgt::CullMode) -> web_sys::GpuCullMode {
@@ -593,14 +609,15 @@ fn map_texture_view_dimension(
 }
 
 fn map_buffer_copy_view(view: crate::BufferCopyView) -> web_sys::GpuBufferCopyView {
-    let mut mapped = web_sys::GpuBufferCopyView::new(&view.buffer.id.0, view.layout.bytes_per_row);
+    let mut mapped =
+        web_sys::GpuBufferCopyView::new(&view.buffer.valid_id().0, view.layout.bytes_per_row);
     mapped.rows_per_image(view.layout.rows_per_image);
     mapped.offset(view.layout.offset as f64);
     mapped
 }
 
 fn map_texture_copy_view(view: crate::TextureCopyView) -> web_sys::GpuTextureCopyView {
-    let mut mapped = web_sys::GpuTextureCopyView::new(&view.texture.id.0);
+    let mut mapped = web_sys::GpuTextureCopyView::new(&view.texture.valid_id().0);
     mapped.mip_level(view.mip_level);
     mapped.origin(&map_origin_3d(view.origin));
     mapped
@@ -642,9 +659,122 @@ fn map_store_op(store: bool) -> web_sys::GpuStoreOp {
     }
 }
 
+fn map_render_pipeline_descriptor(
+    desc: &RenderPipelineDescriptor,
+) -> web_sys::GpuRenderPipelineDescriptor {
+    use web_sys::GpuPrimitiveTopology as pt;
+
+    let mapped_color_states = desc
+        .color_states
+        .iter()
+        .map(|color_state_desc| {
+            let mapped_format = map_texture_format(color_state_desc.format);
+            let mut mapped_color_state_desc = web_sys::GpuColorStateDescriptor::new(mapped_format);
+            mapped_color_state_desc
+                .alpha_blend(&map_blend_descriptor(&color_state_desc.alpha_blend));
+            mapped_color_state_desc
+                .color_blend(&map_blend_descriptor(&color_state_desc.color_blend));
+            mapped_color_state_desc.write_mask(color_state_desc.write_mask.bits());
+            mapped_color_state_desc
+        })
+        .collect::<js_sys::Array>();
+
+    let mapped_primitive_topology = match desc.primitive_topology {
+        wgt::PrimitiveTopology::PointList => pt::PointList,
+        wgt::PrimitiveTopology::LineList => pt::LineList,
+        wgt::PrimitiveTopology::LineStrip => pt::LineStrip,
+        wgt::PrimitiveTopology::TriangleList => pt::TriangleList,
+        wgt::PrimitiveTopology::TriangleStrip => pt::TriangleStrip,
+    };
+
+    let mapped_vertex_stage = map_stage_descriptor(&desc.vertex_stage);
+
+    let mut mapped_desc = web_sys::GpuRenderPipelineDescriptor::new(
+        &desc.layout.as_ref().unwrap().owner.id.0,
+        &mapped_color_states,
+        mapped_primitive_topology,
+        &mapped_vertex_stage,
+    );
+
+    // TODO: label
+
+    if let Some(ref frag) = desc.fragment_stage {
+        mapped_desc.fragment_stage(&map_stage_descriptor(frag));
+    }
+
+    if let Some(ref rasterization) = desc.rasterization_state {
+        mapped_desc.rasterization_state(&map_rasterization_state_descriptor(rasterization));
+    }
+
+    if let Some(ref depth_stencil) = desc.depth_stencil_state {
+        mapped_desc.depth_stencil_state(&map_depth_stencil_state_descriptor(depth_stencil));
+    }
+
+    mapped_desc.vertex_state(&map_vertex_state_descriptor(&desc));
+    mapped_desc.sample_count(desc.sample_count);
+    mapped_desc.sample_mask(desc.sample_mask);
+    mapped_desc.alpha_to_coverage_enabled(desc.alpha_to_coverage_enabled);
+
+    mapped_desc
+}
+
+fn map_compute_pipeline_descriptor(
+    desc: &ComputePipelineDescriptor,
+) -> web_sys::GpuComputePipelineDescriptor {
+    let mapped_compute_stage = map_stage_descriptor(&desc.compute_stage);
+    // TODO: label
+    web_sys::GpuComputePipelineDescriptor::new(
+        &desc.layout.as_ref().unwrap().owner.id.0,
+        &mapped_compute_stage,
+    )
+}
+
+/// Call one of the asynchronous pipeline creation methods of `device`.
+///
+/// Returns `None` if the browser doesn't support it.
+fn create_pipeline_async(
+    device: &web_sys::GpuDevice,
+    method: &str,
+    desc: &JsValue,
+) -> Option<js_sys::Promise> {
+    // `web_sys` doesn't expose these methods yet, so look them up dynamically.
+    let function = js_sys::Reflect::get(device, &JsValue::from_str(method))
+        .ok()?
+        .dyn_into::<js_sys::Function>()
+        .ok()?;
+    Some(function.call1(device, desc).unwrap().into())
+}
+
 type JsFutureResult = Result<wasm_bindgen::JsValue, wasm_bindgen::JsValue>;
 type FutureMap<T> = futures::future::Map<wasm_bindgen_futures::JsFuture, fn(JsFutureResult) -> T>;
 
+fn future_compilation_info(result: JsFutureResult) -> crate::CompilationInfo {
+    let get = |object: &JsValue, key: &str| {
+        js_sys::Reflect::get(object, &JsValue::from_str(key)).unwrap_or(JsValue::UNDEFINED)
+    };
+    // Browsers without `compilationInfo` resolve to `undefined`.
+    let info = match result {
+        Ok(info) if info.is_object() => info,
+        _ => return crate::CompilationInfo::default(),
+    };
+    let messages = js_sys::Array::from(&get(&info, "messages"))
+        .iter()
+        .map(|message| crate::CompilationMessage {
+            message: get(&message, "message").as_string().unwrap_or_default(),
+            message_type: match get(&message, "type").as_string().as_deref() {
+                Some("error") => crate::CompilationMessageType::Error,
+                Some("warning") => crate::CompilationMessageType::Warning,
+                _ => crate::CompilationMessageType::Info,
+            },
+            line_number: get(&message, "lineNum").as_f64().unwrap_or(0.0) as u64,
+            line_position: get(&message, "linePos").as_f64().unwrap_or(0.0) as u64,
+            offset: get(&message, "offset").as_f64().unwrap_or(0.0) as u64,
+            length: get(&message, "length").as_f64().unwrap_or(0.0) as u64,
+        })
+        .collect();
+    crate::CompilationInfo { messages }
+}
+
 fn future_request_adapter(result: JsFutureResult) -> Option<Sendable<web_sys::GpuAdapter>> {
     match result {
         Ok(js_value) => Some(Sendable(web_sys::GpuAdapter::from(js_value))),
@@ -664,6 +794,28 @@ fn future_request_device(
         .map_err(|_| crate::RequestDeviceError)
 }
 
+fn future_create_render_pipeline(
+    result: JsFutureResult,
+) -> Result<Sendable<web_sys::GpuRenderPipeline>, crate::CreatePipelineError> {
+    match result {
+        Ok(js_value) => Ok(Sendable(web_sys::GpuRenderPipeline::from(js_value))),
+        Err(err) => Err(crate::CreatePipelineError {
+            description: format!("{:?}", err),
+        }),
+    }
+}
+
+fn future_create_compute_pipeline(
+    result: JsFutureResult,
+) -> Result<Sendable<web_sys::GpuComputePipeline>, crate::CreatePipelineError> {
+    match result {
+        Ok(js_value) => Ok(Sendable(web_sys::GpuComputePipeline::from(js_value))),
+        Err(err) => Err(crate::CreatePipelineError {
+            description: format!("{:?}", err),
+        }),
+    }
+}
+
 pub(crate) struct MapFuture<T> {
     child: wasm_bindgen_futures::JsFuture,
     buffer: Option<web_sys::GpuBuffer>,
@@ -725,6 +877,11 @@ impl crate::Context for Context {
         FutureMap<Result<(Self::DeviceId, Self::QueueId), crate::RequestDeviceError>>,
     >;
     type MapAsyncFuture = MakeSendFuture<MapFuture<()>>;
+    type CreateRenderPipelineFuture =
+        MakeSendFuture<FutureMap<Result<Self::RenderPipelineId, crate::CreatePipelineError>>>;
+    type CreateComputePipelineFuture =
+        MakeSendFuture<FutureMap<Result<Self::ComputePipelineId, crate::CreatePipelineError>>>;
+    type CompilationInfoFuture = MakeSendFuture<FutureMap<crate::CompilationInfo>>;
 
     fn init(_backends: wgt::BackendBit) -> Self {
         Context(web_sys::window().unwrap().navigator().gpu())
@@ -839,17 +996,27 @@ impl crate::Context for Context {
         &self,
         device: &Self::DeviceId,
         source: ShaderModuleSource,
-    ) -> Self::ShaderModuleId {
+        _specializable: bool,
+    ) -> Result<Self::ShaderModuleId, crate::CreateShaderModuleError> {
         let desc = match source {
             ShaderModuleSource::SpirV(spv) => {
                 web_sys::GpuShaderModuleDescriptor::new(&js_sys::Uint32Array::from(&*spv))
             }
             ShaderModuleSource::Wgsl(_code) => {
-                panic!("WGSL is not yet supported by the Web backend")
+                return Err(crate::CreateShaderModuleError::new(
+                    crate::CompilationMessage {
+                        message: "WGSL is not yet supported by the Web backend".to_string(),
+                        message_type: crate::CompilationMessageType::Error,
+                        line_number: 0,
+                        line_position: 0,
+                        offset: 0,
+                        length: 0,
+                    },
+                ))
             }
         };
         // TODO: label
-        Sendable(device.0.create_shader_module(&desc))
+        Ok(Sendable(device.0.create_shader_module(&desc)))
     }
 
     fn device_create_bind_group_layout(
@@ -946,16 +1113,18 @@ impl crate::Context for Context {
                         size,
                     } => {
                         let mut mapped_buffer_binding =
-                            web_sys::GpuBufferBinding::new(&buffer.id.0);
+                            web_sys::GpuBufferBinding::new(&buffer.valid_id().0);
                         mapped_buffer_binding.offset(offset as f64);
                         if let Some(s) = size {
                             mapped_buffer_binding.size(s.get() as f64);
                         }
                         JsValue::from(mapped_buffer_binding.clone())
                     }
-                    BindingResource::Sampler(ref sampler) => JsValue::from(sampler.id.0.clone()),
+                    BindingResource::Sampler(ref sampler) => {
+                        JsValue::from(sampler.owner.id.0.clone())
+                    }
                     BindingResource::TextureView(ref texture_view) => {
-                        JsValue::from(texture_view.id.0.clone())
+                        JsValue::from(texture_view.valid_id().0.clone())
                     }
                     BindingResource::TextureViewArray(..) => {
                         panic!("Web backend does not support BINDING_INDEXING extension")
@@ -967,7 +1136,7 @@ impl crate::Context for Context {
             .collect::<js_sys::Array>();
 
         let mut mapped_desc =
-            web_sys::GpuBindGroupDescriptor::new(&mapped_entries, &desc.layout.id.0);
+            web_sys::GpuBindGroupDescriptor::new(&mapped_entries, &desc.layout.owner.id.0);
         if let Some(ref label) = desc.label {
             mapped_desc.label(label);
         }
@@ -982,7 +1151,7 @@ impl crate::Context for Context {
         let temp_layouts = desc
             .bind_group_layouts
             .iter()
-            .map(|bgl| bgl.id.0.clone())
+            .map(|bgl| bgl.owner.id.0.clone())
             .collect::<js_sys::Array>();
         let mapped_desc = web_sys::GpuPipelineLayoutDescriptor::new(&temp_layouts);
         // TODO: label
@@ -993,76 +1162,52 @@ impl crate::Context for Context {
         &self,
         device: &Self::DeviceId,
         desc: &RenderPipelineDescriptor,
-    ) -> Self::RenderPipelineId {
-        use web_sys::GpuPrimitiveTopology as pt;
-
-        let mapped_color_states = desc
-            .color_states
-            .iter()
-            .map(|color_state_desc| {
-                let mapped_format = map_texture_format(color_state_desc.format);
-                let mut mapped_color_state_desc =
-                    web_sys::GpuColorStateDescriptor::new(mapped_format);
-                mapped_color_state_desc
-                    .alpha_blend(&map_blend_descriptor(&color_state_desc.alpha_blend));
-                mapped_color_state_desc
-                    .color_blend(&map_blend_descriptor(&color_state_desc.color_blend));
-                mapped_color_state_desc.write_mask(color_state_desc.write_mask.bits());
-                mapped_color_state_desc
-            })
-            .collect::<js_sys::Array>();
-
-        let mapped_primitive_topology = match desc.primitive_topology {
-            wgt::PrimitiveTopology::PointList => pt::PointList,
-            wgt::PrimitiveTopology::LineList => pt::LineList,
-            wgt::PrimitiveTopology::LineStrip => pt::LineStrip,
-            wgt::PrimitiveTopology::TriangleList => pt::TriangleList,
-            wgt::PrimitiveTopology::TriangleStrip => pt::TriangleStrip,
-        };
-
-        let mapped_vertex_stage = map_stage_descriptor(&desc.vertex_stage);
-
-        let mut mapped_desc = web_sys::GpuRenderPipelineDescriptor::new(
-            &desc.layout.as_ref().unwrap().id.0,
-            &mapped_color_states,
-            mapped_primitive_topology,
-            &mapped_vertex_stage,
-        );
-
-        // TODO: label
-
-        if let Some(ref frag) = desc.fragment_stage {
-            mapped_desc.fragment_stage(&map_stage_descriptor(frag));
-        }
-
-        if let Some(ref rasterization) = desc.rasterization_state {
-            mapped_desc.rasterization_state(&map_rasterization_state_descriptor(rasterization));
-        }
-
-        if let Some(ref depth_stencil) = desc.depth_stencil_state {
-            mapped_desc.depth_stencil_state(&map_depth_stencil_state_descriptor(depth_stencil));
-        }
-
-        mapped_desc.vertex_state(&map_vertex_state_descriptor(&desc));
-        mapped_desc.sample_count(desc.sample_count);
-        mapped_desc.sample_mask(desc.sample_mask);
-        mapped_desc.alpha_to_coverage_enabled(desc.alpha_to_coverage_enabled);
-
-        Sendable(device.0.create_render_pipeline(&mapped_desc))
+    ) -> Result<Self::RenderPipelineId, crate::CreatePipelineError> {
+        let mapped_desc = map_render_pipeline_descriptor(desc);
+        Ok(Sendable(device.0.create_render_pipeline(&mapped_desc)))
     }
 
     fn device_create_compute_pipeline(
         &self,
         device: &Self::DeviceId,
         desc: &ComputePipelineDescriptor,
-    ) -> Self::ComputePipelineId {
-        let mapped_compute_stage = map_stage_descriptor(&desc.compute_stage);
-        let mapped_desc = web_sys::GpuComputePipelineDescriptor::new(
-            &desc.layout.as_ref().unwrap().id.0,
-            &mapped_compute_stage,
-        );
-        // TODO: label
-        Sendable(device.0.create_compute_pipeline(&mapped_desc))
+    ) -> Result<Self::ComputePipelineId, crate::CreatePipelineError> {
+        let mapped_desc = map_compute_pipeline_descriptor(desc);
+        Ok(Sendable(device.0.create_compute_pipeline(&mapped_desc)))
+    }
+
+    fn device_create_render_pipeline_async(
+        self: Arc<Self>,
+        device: &Self::DeviceId,
+        desc: &RenderPipelineDescriptor,
+    ) -> Self::CreateRenderPipelineFuture {
+        let mapped_desc = map_render_pipeline_descriptor(desc);
+        let pipeline_promise =
+            create_pipeline_async(&device.0, "createRenderPipelineAsync", &mapped_desc)
+                .unwrap_or_else(|| {
+                    js_sys::Promise::resolve(&device.0.create_render_pipeline(&mapped_desc))
+                });
+        MakeSendFuture(
+            wasm_bindgen_futures::JsFuture::from(pipeline_promise)
+                .map(future_create_render_pipeline),
+        )
+    }
+
+    fn device_create_compute_pipeline_async(
+        self: Arc<Self>,
+        device: &Self::DeviceId,
+        desc: &ComputePipelineDescriptor,
+    ) -> Self::CreateComputePipelineFuture {
+        let mapped_desc = map_compute_pipeline_descriptor(desc);
+        let pipeline_promise =
+            create_pipeline_async(&device.0, "createComputePipelineAsync", &mapped_desc)
+                .unwrap_or_else(|| {
+                    js_sys::Promise::resolve(&device.0.create_compute_pipeline(&mapped_desc))
+                });
+        MakeSendFuture(
+            wasm_bindgen_futures::JsFuture::from(pipeline_promise)
+                .map(future_create_compute_pipeline),
+        )
     }
 
     fn device_create_buffer(
@@ -1232,6 +1377,9 @@ impl crate::Context for Context {
         Sendable(texture.0.create_view_with_descriptor(&mapped))
     }
 
+    fn texture_destroy(&self, texture: &Self::TextureId) {
+        texture.0.destroy();
+    }
     fn texture_drop(&self, _texture: &Self::TextureId) {
         // Dropped automatically
     }
@@ -1241,6 +1389,9 @@ impl crate::Context for Context {
     fn sampler_drop(&self, _sampler: &Self::SamplerId) {
         // Dropped automatically
     }
+    fn buffer_destroy(&self, buffer: &Self::BufferId) {
+        buffer.0.destroy();
+    }
     fn buffer_drop(&self, _buffer: &Self::BufferId) {
         // Dropped automatically
     }
@@ -1253,6 +1404,28 @@ impl crate::Context for Context {
     fn pipeline_layout_drop(&self, _pipeline_layout: &Self::PipelineLayoutId) {
         // Dropped automatically
     }
+    fn shader_module_fragment_outputs(
+        &self,
+        _shader_module: &Self::ShaderModuleId,
+        _entry_point: &str,
+    ) -> Option<Vec<crate::FragmentOutput>> {
+        // Shader reflection is not available on the web.
+        None
+    }
+
+    fn shader_module_compilation_info(
+        &self,
+        shader_module: &Self::ShaderModuleId,
+    ) -> Self::CompilationInfoFuture {
+        // `web_sys` doesn't expose this method yet, so look it up dynamically.
+        let promise = js_sys::Reflect::get(&shader_module.0, &JsValue::from_str("compilationInfo"))
+            .ok()
+            .and_then(|function| function.dyn_into::<js_sys::Function>().ok())
+            .map(|function| function.call0(&shader_module.0).unwrap().into())
+            .unwrap_or_else(|| js_sys::Promise::resolve(&JsValue::UNDEFINED));
+        MakeSendFuture(wasm_bindgen_futures::JsFuture::from(promise).map(future_compilation_info))
+    }
+
     fn shader_module_drop(&self, _shader_module: &Self::ShaderModuleId) {
         // Dropped automatically
     }
@@ -1347,9 +1520,10 @@ impl crate::Context for Context {
     fn command_encoder_begin_compute_pass(
         &self,
         encoder: &Self::CommandEncoderId,
+        desc: &crate::ComputePassDescriptor,
     ) -> Self::ComputePassId {
         let mut mapped_desc = web_sys::GpuComputePassDescriptor::new();
-        if let Some(ref label) = encoder.label() {
+        if let Some(label) = desc.label {
             mapped_desc.label(label);
         }
         ComputePass(encoder.begin_compute_pass_with_descriptor(&mapped_desc))
@@ -1374,7 +1548,7 @@ impl crate::Context for Context {
             .map(|ca| {
                 let mut mapped_color_attachment =
                     web_sys::GpuRenderPassColorAttachmentDescriptor::new(
-                        &ca.attachment.id.0,
+                        &ca.attachment.valid_id().0,
                         &match ca.ops.load {
                             LoadOp::Clear(color) => wasm_bindgen::JsValue::from(map_color(color)),
                             LoadOp::Load => wasm_bindgen::JsValue::from(web_sys::GpuLoadOp::Load),
@@ -1382,7 +1556,7 @@ impl crate::Context for Context {
                     );
 
                 if let Some(rt) = ca.resolve_target {
-                    mapped_color_attachment.resolve_target(&rt.id.0);
+                    mapped_color_attachment.resolve_target(&rt.valid_id().0);
                 }
 
                 mapped_color_attachment.store_op(map_store_op(ca.ops.store));
@@ -1392,8 +1566,9 @@ impl crate::Context for Context {
             .collect::<js_sys::Array>();
 
         let mut mapped_desc = web_sys::GpuRenderPassDescriptor::new(&mapped_color_attachments);
-
-        // TODO: label
+        if let Some(label) = desc.label {
+            mapped_desc.label(label);
+        }
 
         if let Some(dsa) = &desc.depth_stencil_attachment {
             let (depth_load_op, depth_store_op) = match dsa.depth_ops {
@@ -1424,7 +1599,7 @@ impl crate::Context for Context {
             };
             let mapped_depth_stencil_attachment =
                 web_sys::GpuRenderPassDepthStencilAttachmentDescriptor::new(
-                    &dsa.attachment.id.0,
+                    &dsa.attachment.valid_id().0,
                     &depth_load_op,
                     depth_store_op,
                     &stencil_load_op,
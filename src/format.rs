@@ -0,0 +1,230 @@
+use crate::{TextureComponentType, TextureFormat};
+
+/// Properties of a [`TextureFormat`], as returned by [`format_info`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FormatInfo {
+    /// Width and height in texels of a block. Only compressed formats have blocks larger than
+    /// a single texel, and copies of them must be aligned to whole blocks.
+    pub block_dimensions: (u32, u32),
+    /// Size in bytes of a block, which is the size of a texel for uncompressed formats.
+    ///
+    /// `Depth24Plus` and `Depth24PlusStencil8` have an implementation-defined size, given here
+    /// as 4 bytes, and can't be copied to or from buffers.
+    pub block_size: u32,
+    /// Number of components of a texel.
+    pub components: u32,
+    /// Type of the components, as seen by shaders.
+    pub component_type: TextureComponentType,
+    /// Whether the color components are encoded as sRGB, and converted to linear when read.
+    pub srgb: bool,
+    /// Whether the format has a depth aspect.
+    pub has_depth: bool,
+    /// Whether the format has a stencil aspect.
+    pub has_stencil: bool,
+    /// Whether textures of this format can be sampled with linear filtering.
+    pub filterable: bool,
+    /// Whether textures of this format can be used as color or depth-stencil attachments.
+    pub renderable: bool,
+    /// Whether color attachments of this format support blending.
+    pub blendable: bool,
+    /// Whether textures of this format can be bound as storage textures.
+    pub storage: bool,
+}
+
+impl FormatInfo {
+    /// Returns whether the format is block-compressed.
+    pub fn is_compressed(&self) -> bool {
+        self.block_dimensions != (1, 1)
+    }
+
+    /// Returns whether the format has a color aspect.
+    pub fn is_color(&self) -> bool {
+        !self.has_depth && !self.has_stencil
+    }
+
+    /// Returns the number of bytes of a row of `width` texels, rounded up to whole blocks.
+    pub fn bytes_per_row(&self, width: u32) -> u32 {
+        let (block_width, _) = self.block_dimensions;
+        (width + block_width - 1) / block_width * self.block_size
+    }
+}
+
+/// Returns the properties of a texture format.
+pub fn format_info(format: TextureFormat) -> FormatInfo {
+    use TextureFormat::*;
+
+    let (block_size, components, renderable, storage) = match format {
+        R8Unorm | R8Uint | R8Sint => (1, 1, true, false),
+        R8Snorm => (1, 1, false, false),
+        R16Uint | R16Sint | R16Float => (2, 1, true, false),
+        Rg8Unorm | Rg8Uint | Rg8Sint => (2, 2, true, false),
+        Rg8Snorm => (2, 2, false, false),
+        R32Uint | R32Sint | R32Float => (4, 1, true, true),
+        Rg16Uint | Rg16Sint | Rg16Float => (4, 2, true, false),
+        Rgba8Unorm | Rgba8Uint | Rgba8Sint => (4, 4, true, true),
+        Rgba8UnormSrgb | Bgra8Unorm | Bgra8UnormSrgb | Rgb10a2Unorm => (4, 4, true, false),
+        Rgba8Snorm => (4, 4, false, true),
+        Rg11b10Float => (4, 3, false, false),
+        Rg32Uint | Rg32Sint | Rg32Float => (8, 2, true, true),
+        Rgba16Uint | Rgba16Sint | Rgba16Float => (8, 4, true, true),
+        Rgba32Uint | Rgba32Sint | Rgba32Float => (16, 4, true, true),
+        Depth32Float | Depth24Plus => (4, 1, true, false),
+        Depth24PlusStencil8 => (4, 2, true, false),
+        Bc1RgbaUnorm | Bc1RgbaUnormSrgb => (8, 4, false, false),
+        Bc2RgbaUnorm | Bc2RgbaUnormSrgb | Bc3RgbaUnorm | Bc3RgbaUnormSrgb => (16, 4, false, false),
+        Bc4RUnorm | Bc4RSnorm => (8, 1, false, false),
+        Bc5RgUnorm | Bc5RgSnorm => (16, 2, false, false),
+        Bc6hRgbUfloat | Bc6hRgbSfloat => (16, 3, false, false),
+        Bc7RgbaUnorm | Bc7RgbaUnormSrgb => (16, 4, false, false),
+    };
+    let block_dimensions = match format {
+        Bc1RgbaUnorm | Bc1RgbaUnormSrgb | Bc2RgbaUnorm | Bc2RgbaUnormSrgb | Bc3RgbaUnorm
+        | Bc3RgbaUnormSrgb | Bc4RUnorm | Bc4RSnorm | Bc5RgUnorm | Bc5RgSnorm | Bc6hRgbUfloat
+        | Bc6hRgbSfloat | Bc7RgbaUnorm | Bc7RgbaUnormSrgb => (4, 4),
+        _ => (1, 1),
+    };
+    let srgb = matches!(
+        format,
+        Rgba8UnormSrgb
+            | Bgra8UnormSrgb
+            | Bc1RgbaUnormSrgb
+            | Bc2RgbaUnormSrgb
+            | Bc3RgbaUnormSrgb
+            | Bc7RgbaUnormSrgb
+    );
+    let has_depth = matches!(format, Depth32Float | Depth24Plus | Depth24PlusStencil8);
+    let has_stencil = format == Depth24PlusStencil8;
+    let component_type = TextureComponentType::from(format);
+    // 32-bit floats can't be filtered or blended without extensions.
+    let filterable = component_type == TextureComponentType::Float
+        && !has_depth
+        && !matches!(format, R32Float | Rg32Float | Rgba32Float);
+
+    FormatInfo {
+        block_dimensions,
+        block_size,
+        components,
+        component_type,
+        srgb,
+        has_depth,
+        has_stencil,
+        filterable,
+        renderable,
+        blendable: renderable && filterable,
+        storage,
+    }
+}
+
+/// Returns the format declared after `format`, so that walking from `R8Unorm` visits all of
+/// them. The match is exhaustive, so that new formats can't be left out.
+#[cfg(test)]
+fn next_format(format: TextureFormat) -> Option<TextureFormat> {
+    use TextureFormat::*;
+
+    match format {
+        R8Unorm => Some(R8Snorm),
+        R8Snorm => Some(R8Uint),
+        R8Uint => Some(R8Sint),
+        R8Sint => Some(R16Uint),
+        R16Uint => Some(R16Sint),
+        R16Sint => Some(R16Float),
+        R16Float => Some(Rg8Unorm),
+        Rg8Unorm => Some(Rg8Snorm),
+        Rg8Snorm => Some(Rg8Uint),
+        Rg8Uint => Some(Rg8Sint),
+        Rg8Sint => Some(R32Uint),
+        R32Uint => Some(R32Sint),
+        R32Sint => Some(R32Float),
+        R32Float => Some(Rg16Uint),
+        Rg16Uint => Some(Rg16Sint),
+        Rg16Sint => Some(Rg16Float),
+        Rg16Float => Some(Rgba8Unorm),
+        Rgba8Unorm => Some(Rgba8UnormSrgb),
+        Rgba8UnormSrgb => Some(Rgba8Snorm),
+        Rgba8Snorm => Some(Rgba8Uint),
+        Rgba8Uint => Some(Rgba8Sint),
+        Rgba8Sint => Some(Bgra8Unorm),
+        Bgra8Unorm => Some(Bgra8UnormSrgb),
+        Bgra8UnormSrgb => Some(Rgb10a2Unorm),
+        Rgb10a2Unorm => Some(Rg11b10Float),
+        Rg11b10Float => Some(Rg32Uint),
+        Rg32Uint => Some(Rg32Sint),
+        Rg32Sint => Some(Rg32Float),
+        Rg32Float => Some(Rgba16Uint),
+        Rgba16Uint => Some(Rgba16Sint),
+        Rgba16Sint => Some(Rgba16Float),
+        Rgba16Float => Some(Rgba32Uint),
+        Rgba32Uint => Some(Rgba32Sint),
+        Rgba32Sint => Some(Rgba32Float),
+        Rgba32Float => Some(Depth32Float),
+        Depth32Float => Some(Depth24Plus),
+        Depth24Plus => Some(Depth24PlusStencil8),
+        Depth24PlusStencil8 => Some(Bc1RgbaUnorm),
+        Bc1RgbaUnorm => Some(Bc1RgbaUnormSrgb),
+        Bc1RgbaUnormSrgb => Some(Bc2RgbaUnorm),
+        Bc2RgbaUnorm => Some(Bc2RgbaUnormSrgb),
+        Bc2RgbaUnormSrgb => Some(Bc3RgbaUnorm),
+        Bc3RgbaUnorm => Some(Bc3RgbaUnormSrgb),
+        Bc3RgbaUnormSrgb => Some(Bc4RUnorm),
+        Bc4RUnorm => Some(Bc4RSnorm),
+        Bc4RSnorm => Some(Bc5RgUnorm),
+        Bc5RgUnorm => Some(Bc5RgSnorm),
+        Bc5RgSnorm => Some(Bc6hRgbUfloat),
+        Bc6hRgbUfloat => Some(Bc6hRgbSfloat),
+        Bc6hRgbSfloat => Some(Bc7RgbaUnorm),
+        Bc7RgbaUnorm => Some(Bc7RgbaUnormSrgb),
+        Bc7RgbaUnormSrgb => None,
+    }
+}
+
+#[test]
+fn test_all_formats_are_consistent() {
+    let mut visited = Vec::new();
+    let mut next = Some(TextureFormat::R8Unorm);
+    while let Some(format) = next {
+        assert!(!visited.contains(&format), "{:?}", format);
+        visited.push(format);
+        next = next_format(format);
+        let info = format_info(format);
+        assert!((1..=4).contains(&info.components), "{:?}", format);
+        assert!(info.block_size.is_power_of_two(), "{:?}", format);
+        assert!(!info.has_stencil || info.has_depth, "{:?}", format);
+        assert!(!info.srgb || info.component_type == TextureComponentType::Float);
+        if info.is_compressed() {
+            assert_eq!(info.block_dimensions, (4, 4), "{:?}", format);
+            assert!(!info.renderable && !info.storage, "{:?}", format);
+        } else if info.is_color() {
+            // Uncompressed color components are at least a byte each.
+            assert!(info.block_size >= info.components, "{:?}", format);
+        }
+        assert!(!info.blendable || info.renderable, "{:?}", format);
+        assert!(!info.storage || !info.srgb, "{:?}", format);
+    }
+}
+
+#[test]
+fn test_format_info() {
+    use TextureFormat::*;
+
+    let info = format_info(Rgba8UnormSrgb);
+    assert_eq!((info.block_size, info.components), (4, 4));
+    assert!(info.srgb && info.filterable && info.renderable && info.blendable);
+    assert!(!info.storage);
+
+    let info = format_info(Rgba32Float);
+    assert!(info.storage && info.renderable);
+    assert!(!info.filterable && !info.blendable);
+
+    let info = format_info(R32Uint);
+    assert_eq!(info.component_type, TextureComponentType::Uint);
+    assert!(info.renderable && !info.blendable);
+
+    let info = format_info(Depth24PlusStencil8);
+    assert!(info.has_depth && info.has_stencil && !info.is_color());
+    assert!(info.renderable && !info.filterable);
+
+    let info = format_info(Bc1RgbaUnormSrgb);
+    assert_eq!((info.block_dimensions, info.block_size), ((4, 4), 8));
+    assert_eq!(info.bytes_per_row(10), 24);
+    assert!(info.srgb && info.is_compressed() && !info.renderable);
+}
@@ -1,11 +1,16 @@
 //! A cross-platform graphics and compute library based on [WebGPU](https://gpuweb.github.io/gpuweb/).
 //!
 //! To start using the API, create an [`Instance`].
+//!
+//! Resource handles, like [`Buffer`] or [`BindGroup`], are reference counted: cloning one gives
+//! another handle to the same resource, which is destroyed once all of its handles are dropped.
+//! Weak handles, like [`WeakBuffer`], can be used to refer to a resource without keeping it alive.
 
 #![doc(html_logo_url = "https://raw.githubusercontent.com/gfx-rs/wgpu-rs/master/logo.png")]
 #![warn(missing_docs)]
 
 mod backend;
+mod format;
 pub mod util;
 #[macro_use]
 mod macros;
@@ -18,7 +23,10 @@ use std::{
     marker::PhantomData,
     num::{NonZeroU32, NonZeroU8},
     ops::{Bound, Range, RangeBounds},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
     thread,
 };
 
@@ -31,6 +39,8 @@ use serde::Serialize;
 
 #[cfg(not(target_arch = "wasm32"))]
 pub use wgc::instance::{AdapterInfo, DeviceType};
+#[cfg(feature = "derive")]
+pub use wgpu_macros::VertexLayout;
 pub use wgt::{
     AddressMode, Backend, BackendBit, BindGroupLayoutEntry, BindingType, BlendDescriptor,
     BlendFactor, BlendOperation, BufferAddress, BufferSize, BufferUsage, Color,
@@ -151,24 +161,24 @@ trait RenderPassInner<Ctx: Context>: RenderInner<Ctx> {
 
 trait Context: Debug + Send + Sized + Sync {
     type AdapterId: Debug + Send + Sync + 'static;
-    type DeviceId: Debug + Send + Sync + 'static;
+    type DeviceId: Clone + Debug + Send + Sync + 'static;
     type QueueId: Debug + Send + Sync + 'static;
     type ShaderModuleId: Debug + Send + Sync + 'static;
-    type BindGroupLayoutId: Debug + Send + Sync + 'static;
-    type BindGroupId: Debug + Send + Sync + 'static;
-    type TextureViewId: Debug + Send + Sync + 'static;
-    type SamplerId: Debug + Send + Sync + 'static;
-    type BufferId: Debug + Send + Sync + 'static;
-    type TextureId: Debug + Send + Sync + 'static;
-    type PipelineLayoutId: Debug + Send + Sync + 'static;
-    type RenderPipelineId: Debug + Send + Sync + 'static;
-    type ComputePipelineId: Debug + Send + Sync + 'static;
+    type BindGroupLayoutId: Clone + Debug + Send + Sync + 'static;
+    type BindGroupId: Clone + Debug + Send + Sync + 'static;
+    type TextureViewId: Clone + Debug + Send + Sync + 'static;
+    type SamplerId: Clone + Debug + Send + Sync + 'static;
+    type BufferId: Clone + Debug + Send + Sync + 'static;
+    type TextureId: Clone + Debug + Send + Sync + 'static;
+    type PipelineLayoutId: Clone + Debug + Send + Sync + 'static;
+    type RenderPipelineId: Clone + Debug + Send + Sync + 'static;
+    type ComputePipelineId: Clone + Debug + Send + Sync + 'static;
     type CommandEncoderId: Debug;
     type ComputePassId: Debug + ComputePassInner<Self>;
     type RenderPassId: Debug + RenderPassInner<Self>;
     type CommandBufferId: Debug + Send + Sync;
     type RenderBundleEncoderId: Debug + RenderInner<Self>;
-    type RenderBundleId: Debug + Send + Sync + 'static;
+    type RenderBundleId: Clone + Debug + Send + Sync + 'static;
     type SurfaceId: Debug + Send + Sync + 'static;
     type SwapChainId: Debug + Send + Sync + 'static;
 
@@ -178,6 +188,11 @@ trait Context: Debug + Send + Sized + Sync {
     type RequestDeviceFuture: Future<Output = Result<(Self::DeviceId, Self::QueueId), RequestDeviceError>>
         + Send;
     type MapAsyncFuture: Future<Output = Result<(), BufferAsyncError>> + Send;
+    type CreateRenderPipelineFuture: Future<Output = Result<Self::RenderPipelineId, CreatePipelineError>>
+        + Send;
+    type CreateComputePipelineFuture: Future<Output = Result<Self::ComputePipelineId, CreatePipelineError>>
+        + Send;
+    type CompilationInfoFuture: Future<Output = CompilationInfo> + Send;
 
     fn init(backends: BackendBit) -> Self;
     fn instance_create_surface(
@@ -209,7 +224,8 @@ trait Context: Debug + Send + Sized + Sync {
         &self,
         device: &Self::DeviceId,
         source: ShaderModuleSource,
-    ) -> Self::ShaderModuleId;
+        specializable: bool,
+    ) -> Result<Self::ShaderModuleId, CreateShaderModuleError>;
     fn device_create_bind_group_layout(
         &self,
         device: &Self::DeviceId,
@@ -229,12 +245,22 @@ trait Context: Debug + Send + Sized + Sync {
         &self,
         device: &Self::DeviceId,
         desc: &RenderPipelineDescriptor,
-    ) -> Self::RenderPipelineId;
+    ) -> Result<Self::RenderPipelineId, CreatePipelineError>;
     fn device_create_compute_pipeline(
         &self,
         device: &Self::DeviceId,
         desc: &ComputePipelineDescriptor,
-    ) -> Self::ComputePipelineId;
+    ) -> Result<Self::ComputePipelineId, CreatePipelineError>;
+    fn device_create_render_pipeline_async(
+        self: Arc<Self>,
+        device: &Self::DeviceId,
+        desc: &RenderPipelineDescriptor,
+    ) -> Self::CreateRenderPipelineFuture;
+    fn device_create_compute_pipeline_async(
+        self: Arc<Self>,
+        device: &Self::DeviceId,
+        desc: &ComputePipelineDescriptor,
+    ) -> Self::CreateComputePipelineFuture;
     fn device_create_buffer(
         &self,
         device: &Self::DeviceId,
@@ -296,13 +322,24 @@ trait Context: Debug + Send + Sized + Sync {
         texture: &Self::TextureId,
         desc: &TextureViewDescriptor,
     ) -> Self::TextureViewId;
+    fn texture_destroy(&self, texture: &Self::TextureId);
     fn texture_drop(&self, texture: &Self::TextureId);
     fn texture_view_drop(&self, texture_view: &Self::TextureViewId);
     fn sampler_drop(&self, sampler: &Self::SamplerId);
+    fn buffer_destroy(&self, buffer: &Self::BufferId);
     fn buffer_drop(&self, buffer: &Self::BufferId);
     fn bind_group_drop(&self, bind_group: &Self::BindGroupId);
     fn bind_group_layout_drop(&self, bind_group_layout: &Self::BindGroupLayoutId);
     fn pipeline_layout_drop(&self, pipeline_layout: &Self::PipelineLayoutId);
+    fn shader_module_fragment_outputs(
+        &self,
+        shader_module: &Self::ShaderModuleId,
+        entry_point: &str,
+    ) -> Option<Vec<FragmentOutput>>;
+    fn shader_module_compilation_info(
+        &self,
+        shader_module: &Self::ShaderModuleId,
+    ) -> Self::CompilationInfoFuture;
     fn shader_module_drop(&self, shader_module: &Self::ShaderModuleId);
     fn command_buffer_drop(&self, command_buffer: &Self::CommandBufferId);
     fn render_bundle_drop(&self, render_bundle: &Self::RenderBundleId);
@@ -354,6 +391,7 @@ trait Context: Debug + Send + Sized + Sync {
     fn command_encoder_begin_compute_pass(
         &self,
         encoder: &Self::CommandEncoderId,
+        desc: &ComputePassDescriptor,
     ) -> Self::ComputePassId;
     fn command_encoder_end_compute_pass(
         &self,
@@ -436,8 +474,15 @@ pub struct Adapter {
 pub struct Device {
     context: Arc<C>,
     id: <C as Context>::DeviceId,
+    zero_buffer: ZeroBuffer,
 }
 
+/// Zero-filled buffer of a device, shared with its command encoders to clear resources.
+type ZeroBuffer = Arc<Mutex<Option<Buffer>>>;
+
+/// Largest size of a zero-filled buffer. Larger clears are split into several copies.
+const ZERO_BUFFER_MAX_SIZE: BufferAddress = 1 << 20;
+
 /// Passed to [`Device::poll`] to control if it should block or not. This has no effect on
 /// the web.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -481,7 +526,12 @@ impl MapContext {
             Some(s) => offset + s.get(),
             None => self.initial_range.end,
         };
-        assert!(self.initial_range.start <= offset && end <= self.initial_range.end);
+        assert!(
+            self.initial_range.start <= offset && end <= self.initial_range.end,
+            "Range {:?} is outside of the mapped range {:?}",
+            offset..end,
+            self.initial_range
+        );
         for sub in self.sub_ranges.iter() {
             assert!(
                 end <= sub.start || offset >= sub.end,
@@ -509,15 +559,137 @@ impl MapContext {
     }
 }
 
+/// Shared ownership of a resource, which is dropped once the last handle to it is gone.
+struct Owner<I> {
+    context: Arc<C>,
+    id: I,
+    /// `None` if the resource is owned by something else, like a swap chain.
+    drop_fn: Option<fn(&C, &I)>,
+    /// Set once the resource is explicitly destroyed, or released by its owner, after which it
+    /// isn't dropped anymore.
+    destroyed: AtomicBool,
+}
+
+impl<I> Owner<I> {
+    fn new(context: &Arc<C>, id: I, drop_fn: fn(&C, &I)) -> Arc<Self> {
+        Arc::new(Owner {
+            context: Arc::clone(context),
+            id,
+            drop_fn: Some(drop_fn),
+            destroyed: AtomicBool::new(false),
+        })
+    }
+
+    fn unowned(context: &Arc<C>, id: I) -> Arc<Self> {
+        Arc::new(Owner {
+            context: Arc::clone(context),
+            id,
+            drop_fn: None,
+            destroyed: AtomicBool::new(false),
+        })
+    }
+
+    /// Destroys the resource, unless it already was.
+    fn destroy(&self, destroy_fn: fn(&C, &I)) {
+        if !self.destroyed.swap(true, Ordering::AcqRel) {
+            destroy_fn(&self.context, &self.id);
+        }
+    }
+
+    fn is_destroyed(&self) -> bool {
+        self.destroyed.load(Ordering::Acquire)
+    }
+
+    /// Marks a resource owned by something else as released, after which it can't be used.
+    fn expire(&self) {
+        self.destroyed.store(true, Ordering::Release);
+    }
+
+    /// Returns `true` if the resource is owned by something else, and was released.
+    fn is_expired(&self) -> bool {
+        self.drop_fn.is_none() && self.is_destroyed()
+    }
+}
+
+impl<I: Debug> Debug for Owner<I> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Owner")
+            .field("id", &self.id)
+            .field("owned", &self.drop_fn.is_some())
+            .field("destroyed", &self.destroyed)
+            .finish()
+    }
+}
+
+impl<I> Drop for Owner<I> {
+    fn drop(&mut self) {
+        if let Some(drop_fn) = self.drop_fn {
+            if !*self.destroyed.get_mut() && !thread::panicking() {
+                drop_fn(&self.context, &self.id);
+            }
+        }
+    }
+}
+
+/// Declares the weak handle type of a resource, along with `downgrade` and `upgrade`.
+///
+/// The listed fields are kept by the weak handle, and cloned into the upgraded handle.
+macro_rules! weak_handle {
+    (
+        $(#[$attr:meta])*
+        $weak:ident => $handle:ident($id:ty) {
+            $($field:ident: $ty:ty),* $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Clone, Debug)]
+        pub struct $weak {
+            owner: std::sync::Weak<Owner<$id>>,
+            $($field: $ty,)*
+        }
+
+        impl $handle {
+            /// Creates a weak handle to this resource, which doesn't keep it alive.
+            pub fn downgrade(&self) -> $weak {
+                $weak {
+                    owner: Arc::downgrade(&self.owner),
+                    $($field: self.$field.clone(),)*
+                }
+            }
+        }
+
+        impl $weak {
+            /// Returns a handle to the resource, or `None` if all of its handles were dropped.
+            pub fn upgrade(&self) -> Option<$handle> {
+                let owner = self.owner.upgrade()?;
+                if owner.is_expired() {
+                    return None;
+                }
+                Some($handle {
+                    owner,
+                    $($field: self.$field.clone(),)*
+                })
+            }
+        }
+    };
+}
+
 /// Handle to a GPU-accessible buffer.
 ///
 /// Created with [`Device::create_buffer`] or [`Device::create_buffer_init`]
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Buffer {
-    context: Arc<C>,
-    id: <C as Context>::BufferId,
-    map_context: Mutex<MapContext>,
-    usage: BufferUsage,
+    owner: Arc<Owner<<C as Context>::BufferId>>,
+    map_context: Arc<Mutex<MapContext>>,
+    descriptor: BufferDescriptor<'static>,
+}
+
+weak_handle! {
+    /// Weak handle to a [`Buffer`], obtained with [`Buffer::downgrade`].
+    WeakBuffer => Buffer(<C as Context>::BufferId) {
+        map_context: Arc<Mutex<MapContext>>,
+        descriptor: BufferDescriptor<'static>,
+    }
 }
 
 /// Slice into a [`Buffer`].
@@ -535,22 +707,38 @@ pub struct BufferSlice<'a> {
 /// Handle to a texture on the GPU.
 ///
 /// Created by calling [`Device::create_texture`]
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Texture {
-    context: Arc<C>,
-    id: <C as Context>::TextureId,
-    owned: bool,
+    owner: Arc<Owner<<C as Context>::TextureId>>,
+    descriptor: TextureDescriptor<'static>,
+}
+
+weak_handle! {
+    /// Weak handle to a [`Texture`], obtained with [`Texture::downgrade`].
+    WeakTexture => Texture(<C as Context>::TextureId) {
+        descriptor: TextureDescriptor<'static>,
+    }
 }
 
 /// Handle to a texture view.
 ///
 /// A `TextureView` object describes a texture and associated metadata needed by a
 /// [`RenderPipeline`] or [`BindGroup`].
-#[derive(Debug)]
+///
+/// The view of a [`SwapChainTexture`], and its clones, are only valid until the frame is
+/// presented. Using them afterwards panics, and their weak handles can't be upgraded anymore.
+#[derive(Clone, Debug)]
 pub struct TextureView {
-    context: Arc<C>,
-    id: <C as Context>::TextureViewId,
-    owned: bool,
+    owner: Arc<Owner<<C as Context>::TextureViewId>>,
+    /// Descriptor with all the optional fields resolved.
+    descriptor: TextureViewDescriptor<'static>,
+}
+
+weak_handle! {
+    /// Weak handle to a [`TextureView`], obtained with [`TextureView::downgrade`].
+    WeakTextureView => TextureView(<C as Context>::TextureViewId) {
+        descriptor: TextureViewDescriptor<'static>,
+    }
 }
 
 /// Handle to a sampler.
@@ -558,17 +746,16 @@ pub struct TextureView {
 /// A `Sampler` object defines how a pipeline will sample from a [`TextureView`]. Samplers define
 /// image filters (including anisotropy) and address (wrapping) modes, among other things. See
 /// the documentation for [`SamplerDescriptor`] for more information.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Sampler {
-    context: Arc<C>,
-    id: <C as Context>::SamplerId,
+    owner: Arc<Owner<<C as Context>::SamplerId>>,
+    descriptor: SamplerDescriptor<'static>,
 }
 
-impl Drop for Sampler {
-    fn drop(&mut self) {
-        if !thread::panicking() {
-            self.context.sampler_drop(&self.id);
-        }
+weak_handle! {
+    /// Weak handle to a [`Sampler`], obtained with [`Sampler::downgrade`].
+    WeakSampler => Sampler(<C as Context>::SamplerId) {
+        descriptor: SamplerDescriptor<'static>,
     }
 }
 
@@ -589,6 +776,7 @@ pub struct Surface {
 pub struct SwapChain {
     context: Arc<C>,
     id: <C as Context>::SwapChainId,
+    format: TextureFormat,
 }
 
 /// Handle to a binding group layout.
@@ -597,17 +785,17 @@ pub struct SwapChain {
 /// create a [`BindGroupDescriptor`] object, which in turn can be used to create a [`BindGroup`]
 /// object with [`Device::create_bind_group`]. A series of `BindGroupLayout`s can also be used to
 /// create a [`PipelineLayoutDescriptor`], which can be used to create a [`PipelineLayout`].
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct BindGroupLayout {
-    context: Arc<C>,
-    id: <C as Context>::BindGroupLayoutId,
+    owner: Arc<Owner<<C as Context>::BindGroupLayoutId>>,
+    /// Entries of the layout, unless it was obtained from a pipeline.
+    entries: Option<Arc<[BindGroupLayoutEntry]>>,
 }
 
-impl Drop for BindGroupLayout {
-    fn drop(&mut self) {
-        if !thread::panicking() {
-            self.context.bind_group_layout_drop(&self.id);
-        }
+weak_handle! {
+    /// Weak handle to a [`BindGroupLayout`], obtained with [`BindGroupLayout::downgrade`].
+    WeakBindGroupLayout => BindGroupLayout(<C as Context>::BindGroupLayoutId) {
+        entries: Option<Arc<[BindGroupLayoutEntry]>>,
     }
 }
 
@@ -617,18 +805,14 @@ impl Drop for BindGroupLayout {
 /// [`BindGroupLayout`]. It can be created with [`Device::create_bind_group`]. A `BindGroup` can
 /// be bound to a particular [`RenderPass`] with [`RenderPass::set_bind_group`], or to a
 /// [`ComputePass`] with [`ComputePass::set_bind_group`].
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct BindGroup {
-    context: Arc<C>,
-    id: <C as Context>::BindGroupId,
+    owner: Arc<Owner<<C as Context>::BindGroupId>>,
 }
 
-impl Drop for BindGroup {
-    fn drop(&mut self) {
-        if !thread::panicking() {
-            self.context.bind_group_drop(&self.id);
-        }
-    }
+weak_handle! {
+    /// Weak handle to a [`BindGroup`], obtained with [`BindGroup::downgrade`].
+    WeakBindGroup => BindGroup(<C as Context>::BindGroupId) {}
 }
 
 /// Handle to a compiled shader module.
@@ -636,17 +820,114 @@ impl Drop for BindGroup {
 /// A `ShaderModule` represents a compiled shader module on the GPU. It can be created by passing
 /// valid SPIR-V source code to [`Device::create_shader_module`]. Shader modules are used to define
 /// programmable stages of a pipeline.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct ShaderModule {
-    context: Arc<C>,
-    id: <C as Context>::ShaderModuleId,
+    /// Its ID is `None` if the module failed to compile.
+    owner: Arc<Owner<Option<<C as Context>::ShaderModuleId>>>,
+    compilation_info: CompilationInfo,
 }
 
-impl Drop for ShaderModule {
-    fn drop(&mut self) {
-        if !thread::panicking() {
-            self.context.shader_module_drop(&self.id);
+weak_handle! {
+    /// Weak handle to a [`ShaderModule`], obtained with [`ShaderModule::downgrade`].
+    WeakShaderModule => ShaderModule(Option<<C as Context>::ShaderModuleId>) {
+        compilation_info: CompilationInfo,
+    }
+}
+
+impl ShaderModule {
+    /// Get the messages produced when compiling this shader module.
+    ///
+    /// If any of them is an error, creating a pipeline using this module will panic.
+    ///
+    /// On native, only errors are reported, since wgpu-core doesn't produce warnings.
+    /// On the web, this maps to `GPUShaderModule.compilationInfo` when the browser
+    /// supports it, and resolves to no messages otherwise.
+    pub fn compilation_info(&self) -> impl Future<Output = CompilationInfo> + Send {
+        match self.owner.id {
+            Some(ref id) => {
+                futures::future::Either::Left(self.owner.context.shader_module_compilation_info(id))
+            }
+            None => futures::future::Either::Right(futures::future::ready(
+                self.compilation_info.clone(),
+            )),
+        }
+    }
+
+    fn valid_id(&self) -> &<C as Context>::ShaderModuleId {
+        match self.owner.id {
+            Some(ref id) => id,
+            None => panic!(
+                "Shader module failed to compile: {}",
+                CreateShaderModuleError {
+                    info: self.compilation_info.clone(),
+                }
+            ),
+        }
+    }
+}
+
+/// Output of a fragment entry point, as found by shader reflection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct FragmentOutput {
+    location: u32,
+    component_type: TextureComponentType,
+}
+
+/// Severity of a [`CompilationMessage`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CompilationMessageType {
+    /// The shader module failed to compile.
+    Error,
+    /// Something suspicious that doesn't prevent compilation.
+    Warning,
+    /// Purely informational.
+    Info,
+}
+
+/// A message produced when compiling a shader module.
+///
+/// Positions are expressed in bytes of the source code. They are all zero
+/// when the message doesn't refer to a specific location, as with SPIR-V.
+/// On native, only WGSL parsing errors have a location: errors reported by
+/// the validation of wgpu-core don't.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CompilationMessage {
+    /// Human readable description of the message.
+    pub message: String,
+    /// Severity of the message.
+    pub message_type: CompilationMessageType,
+    /// Line of the source the message refers to, starting at 1.
+    pub line_number: u64,
+    /// Column of the line the message refers to, starting at 1.
+    pub line_position: u64,
+    /// Offset from the start of the source the message refers to.
+    pub offset: u64,
+    /// Length of the part of the source the message refers to.
+    pub length: u64,
+}
+
+impl Display for CompilationMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.line_number != 0 {
+            write!(f, "{}:{}: ", self.line_number, self.line_position)?;
         }
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Messages produced when compiling a shader module.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct CompilationInfo {
+    /// All messages, in the order they were produced.
+    pub messages: Vec<CompilationMessage>,
+}
+
+impl CompilationInfo {
+    /// Returns `true` if any of the messages is an error.
+    pub fn has_errors(&self) -> bool {
+        self.messages
+            .iter()
+            .any(|message| message.message_type == CompilationMessageType::Error)
     }
 }
 
@@ -660,7 +941,8 @@ pub enum ShaderModuleSource<'a> {
     /// WGSL module as a string slice.
     ///
     /// wgpu-rs will parse it and use for validation. It will attempt
-    /// to build a SPIR-V module internally and panic otherwise.
+    /// to build a SPIR-V module internally, and report parsing errors
+    /// in [`ShaderModule::compilation_info`].
     ///
     /// Note: WGSL is not yet supported on the Web.
     Wgsl(Cow<'a, str>),
@@ -669,47 +951,42 @@ pub enum ShaderModuleSource<'a> {
 /// Handle to a pipeline layout.
 ///
 /// A `PipelineLayout` object describes the available binding groups of a pipeline.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct PipelineLayout {
-    context: Arc<C>,
-    id: <C as Context>::PipelineLayoutId,
+    owner: Arc<Owner<<C as Context>::PipelineLayoutId>>,
 }
 
-impl Drop for PipelineLayout {
-    fn drop(&mut self) {
-        if !thread::panicking() {
-            self.context.pipeline_layout_drop(&self.id);
-        }
-    }
+weak_handle! {
+    /// Weak handle to a [`PipelineLayout`], obtained with [`PipelineLayout::downgrade`].
+    WeakPipelineLayout => PipelineLayout(<C as Context>::PipelineLayoutId) {}
 }
 
 /// Handle to a rendering (graphics) pipeline.
 ///
 /// A `RenderPipeline` object represents a graphics pipeline and its stages, bindings, vertex
 /// buffers and targets. A `RenderPipeline` may be created with [`Device::create_render_pipeline`].
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct RenderPipeline {
-    context: Arc<C>,
-    id: <C as Context>::RenderPipelineId,
+    owner: Arc<Owner<<C as Context>::RenderPipelineId>>,
 }
 
-impl Drop for RenderPipeline {
-    fn drop(&mut self) {
-        if !thread::panicking() {
-            self.context.render_pipeline_drop(&self.id);
-        }
-    }
+weak_handle! {
+    /// Weak handle to a [`RenderPipeline`], obtained with [`RenderPipeline::downgrade`].
+    WeakRenderPipeline => RenderPipeline(<C as Context>::RenderPipelineId) {}
 }
 
 impl RenderPipeline {
     /// Get an object representing the bind group layout at a given index.
     pub fn get_bind_group_layout(&self, index: u32) -> BindGroupLayout {
-        let context = Arc::clone(&self.context);
+        let id = self
+            .owner
+            .context
+            .render_pipeline_get_bind_group_layout(&self.owner.id, index);
         BindGroupLayout {
-            context,
-            id: self
-                .context
-                .render_pipeline_get_bind_group_layout(&self.id, index),
+            owner: Owner::new(&self.owner.context, id, |context, id| {
+                context.bind_group_layout_drop(id)
+            }),
+            entries: None,
         }
     }
 }
@@ -718,29 +995,28 @@ impl RenderPipeline {
 ///
 /// A `ComputePipeline` object represents a compute pipeline and its single shader stage.
 /// A `ComputePipeline` may be created with [`Device::create_compute_pipeline`].
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct ComputePipeline {
-    context: Arc<C>,
-    id: <C as Context>::ComputePipelineId,
+    owner: Arc<Owner<<C as Context>::ComputePipelineId>>,
 }
 
-impl Drop for ComputePipeline {
-    fn drop(&mut self) {
-        if !thread::panicking() {
-            self.context.compute_pipeline_drop(&self.id);
-        }
-    }
+weak_handle! {
+    /// Weak handle to a [`ComputePipeline`], obtained with [`ComputePipeline::downgrade`].
+    WeakComputePipeline => ComputePipeline(<C as Context>::ComputePipelineId) {}
 }
 
 impl ComputePipeline {
     /// Get an object representing the bind group layout at a given index.
     pub fn get_bind_group_layout(&self, index: u32) -> BindGroupLayout {
-        let context = Arc::clone(&self.context);
+        let id = self
+            .owner
+            .context
+            .compute_pipeline_get_bind_group_layout(&self.owner.id, index);
         BindGroupLayout {
-            context,
-            id: self
-                .context
-                .compute_pipeline_get_bind_group_layout(&self.id, index),
+            owner: Owner::new(&self.owner.context, id, |context, id| {
+                context.bind_group_layout_drop(id)
+            }),
+            entries: None,
         }
     }
 }
@@ -777,6 +1053,8 @@ impl Drop for CommandBuffer {
 pub struct CommandEncoder {
     context: Arc<C>,
     id: <C as Context>::CommandEncoderId,
+    device_id: <C as Context>::DeviceId,
+    zero_buffer: ZeroBuffer,
     /// This type should be !Send !Sync, because it represents an allocation on this thread's
     /// command buffer.
     _p: PhantomData<*const u8>,
@@ -787,6 +1065,10 @@ pub struct CommandEncoder {
 pub struct RenderPass<'a> {
     id: <C as Context>::RenderPassId,
     parent: &'a mut CommandEncoder,
+    /// Resources given to the `_owned` functions, kept alive until the pass ends.
+    owned_buffers: Vec<Buffer>,
+    owned_bind_groups: Vec<BindGroup>,
+    owned_pipelines: Vec<RenderPipeline>,
 }
 
 /// In-progress recording of a compute pass.
@@ -818,18 +1100,14 @@ pub struct RenderBundleEncoder<'a> {
 /// can be executed onto a [`CommandEncoder`] using [`RenderPass::execute_bundles`].
 ///
 /// Executing a [`RenderBundle`] is often more efficient then issuing the underlying commands manually.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct RenderBundle {
-    context: Arc<C>,
-    id: <C as Context>::RenderBundleId,
+    owner: Arc<Owner<<C as Context>::RenderBundleId>>,
 }
 
-impl Drop for RenderBundle {
-    fn drop(&mut self) {
-        if !thread::panicking() {
-            self.context.render_bundle_drop(&self.id);
-        }
-    }
+weak_handle! {
+    /// Weak handle to a [`RenderBundle`], obtained with [`RenderBundle::downgrade`].
+    WeakRenderBundle => RenderBundle(<C as Context>::RenderBundleId) {}
 }
 
 /// Handle to a command queue on a device.
@@ -874,6 +1152,9 @@ pub enum BindingResource<'a> {
     ///
     /// Corresponds to [`BindingType::SampledTexture`] and [`BindingType::StorageTexture`] with
     /// [`BindGroupLayoutEntry::count`] set to Some.
+    ///
+    /// Textures are the only resources that can be bound as arrays: wgpu-core rejects layouts
+    /// with arrays of buffers or samplers, and has no feature to enable them.
     TextureViewArray(&'a [TextureView]),
 }
 
@@ -1017,6 +1298,23 @@ pub struct TextureViewDescriptor<'a> {
     pub array_layer_count: Option<NonZeroU32>,
 }
 
+/// Describes the subresources of a [`Texture`] cleared by [`CommandEncoder::clear_texture`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ImageSubresourceRange {
+    /// Aspect of the texture. Color textures must be [`TextureAspect::All`].
+    pub aspect: TextureAspect,
+    /// Base mip level.
+    pub base_mip_level: u32,
+    /// Mip level count.
+    /// If `None`, considered to include the rest of the mipmap levels.
+    pub mip_level_count: Option<NonZeroU32>,
+    /// Base array layer, or depth slice for 3D textures.
+    pub base_array_layer: u32,
+    /// Layer count.
+    /// If `None`, considered to include the rest of the array layers, or depth slices for 3D textures.
+    pub array_layer_count: Option<NonZeroU32>,
+}
+
 /// Describes a pipeline layout.
 ///
 /// A `PipelineLayoutDescriptor` can be used to create a pipeline layout.
@@ -1112,17 +1410,41 @@ pub struct ProgrammableStageDescriptor<'a> {
     /// The name of the entry point in the compiled shader. There must be a function that returns
     /// void with this name in the shader.
     pub entry_point: &'a str,
+    /// Values of pipeline-overridable constants, as pairs of key and value.
+    ///
+    /// For SPIR-V modules, the key is the decimal `SpecId` of a specialization constant.
+    /// For WGSL modules, it is the name or ID of an overridable constant. Values are
+    /// converted to the type of the constant they override.
+    ///
+    /// # Limitations
+    ///
+    /// On native, constants are only supported for SPIR-V modules created with
+    /// [`Device::create_specializable_shader_module`], and creating a pipeline that
+    /// overrides constants of any other module fails. In particular, WGSL modules can't
+    /// override constants on native, since the WGSL parser of wgpu-core has no syntax
+    /// for declaring them. On the web, the map is passed to the browser for modules
+    /// in either language.
+    pub constants: &'a [(&'a str, f64)],
 }
 
 /// Describes the attachments of a render pass.
 #[derive(Clone, Debug, Default)]
 pub struct RenderPassDescriptor<'a, 'b> {
+    /// Debug label of the render pass. This will show up in graphics debuggers for easy identification.
+    pub label: Option<&'a str>,
     /// The color attachments of the render pass.
     pub color_attachments: &'b [RenderPassColorAttachmentDescriptor<'a>],
     /// The depth and stencil attachment of the render pass, if any.
     pub depth_stencil_attachment: Option<RenderPassDepthStencilAttachmentDescriptor<'a>>,
 }
 
+/// Describes a compute pass.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub struct ComputePassDescriptor<'a> {
+    /// Debug label of the compute pass. This will show up in graphics debuggers for easy identification.
+    pub label: Option<&'a str>,
+}
+
 /// Describes a render (graphics) pipeline.
 #[derive(Clone, Debug)]
 pub struct RenderPipelineDescriptor<'a> {
@@ -1208,6 +1530,43 @@ pub struct VertexStateDescriptor<'a> {
     pub vertex_buffers: &'a [VertexBufferDescriptor<'a>],
 }
 
+/// A `#[repr(C)]` type stored in vertex buffers, with a fixed set of attributes.
+///
+/// With the `derive` feature enabled, this can be derived for structs whose fields are
+/// all vertex attributes:
+///
+/// ```
+/// # #[cfg(feature = "derive")] {
+/// #[repr(C)]
+/// #[derive(Clone, Copy, wgpu::VertexLayout)]
+/// struct Vertex {
+///     position: [f32; 3],
+///     #[normalized]
+///     color: [u8; 4],
+/// }
+///
+/// use wgpu::VertexLayout as _;
+/// let layout = Vertex::layout();
+/// assert_eq!(layout.stride, 16);
+/// assert_eq!(layout.attributes[1].format, wgpu::VertexFormat::Uchar4Norm);
+/// # }
+/// ```
+pub trait VertexLayout: Sized {
+    /// How often a vertex buffer of this type is "stepped" forward.
+    const STEP_MODE: InputStepMode;
+    /// The list of attributes which comprise a single vertex.
+    const ATTRIBUTES: &'static [VertexAttributeDescriptor];
+
+    /// Returns the description of a vertex buffer of this type.
+    fn layout() -> VertexBufferDescriptor<'static> {
+        VertexBufferDescriptor {
+            stride: std::mem::size_of::<Self>() as BufferAddress,
+            step_mode: Self::STEP_MODE,
+            attributes: Self::ATTRIBUTES,
+        }
+    }
+}
+
 /// Describes a [`RenderBundleEncoder`].
 #[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
 pub struct RenderBundleEncoderDescriptor<'a> {
@@ -1228,6 +1587,8 @@ pub struct RenderBundleEncoderDescriptor<'a> {
 #[derive(Debug)]
 pub struct SwapChainTexture {
     /// Accessible view of the frame.
+    ///
+    /// It and its clones are only valid until the frame is presented, see [`TextureView`].
     pub view: TextureView,
     detail: <C as Context>::SwapChainOutputDetail,
 }
@@ -1370,6 +1731,7 @@ impl Adapter {
                     Device {
                         context: Arc::clone(&context),
                         id: device_id,
+                        zero_buffer: ZeroBuffer::default(),
                     },
                     Queue {
                         context,
@@ -1426,10 +1788,47 @@ impl Device {
     }
 
     /// Creates a shader module from either SPIR-V or WGSL source code.
+    ///
+    /// Compilation errors are reported by [`ShaderModule::compilation_info`].
+    ///
+    /// On native, WGSL is parsed ahead of wgpu-core with the `wgsl-validation` feature,
+    /// which is enabled by default. Without it, WGSL parsing errors panic.
     pub fn create_shader_module(&self, source: ShaderModuleSource) -> ShaderModule {
+        self.create_shader_module_impl(source, false)
+    }
+
+    /// Creates a shader module whose pipelines can override its constants, see
+    /// [`ProgrammableStageDescriptor::constants`].
+    ///
+    /// On native, the SPIR-V code is kept alongside the module, in order to create
+    /// specialized copies of it when pipelines are created. It is also reflected the first
+    /// time [`util::RenderPipelineBuilder`] checks color targets against it. On the web,
+    /// this is the same as [`Device::create_shader_module`].
+    pub fn create_specializable_shader_module(&self, source: ShaderModuleSource) -> ShaderModule {
+        self.create_shader_module_impl(source, true)
+    }
+
+    fn create_shader_module_impl(
+        &self,
+        source: ShaderModuleSource,
+        specializable: bool,
+    ) -> ShaderModule {
+        let (id, compilation_info) = match Context::device_create_shader_module(
+            &*self.context,
+            &self.id,
+            source,
+            specializable,
+        ) {
+            Ok(id) => (Some(id), CompilationInfo::default()),
+            Err(err) => (None, err.info),
+        };
         ShaderModule {
-            context: Arc::clone(&self.context),
-            id: Context::device_create_shader_module(&*self.context, &self.id, source),
+            owner: Owner::new(&self.context, id, |context, id| {
+                if let Some(id) = id {
+                    context.shader_module_drop(id);
+                }
+            }),
+            compilation_info,
         }
     }
 
@@ -1438,6 +1837,8 @@ impl Device {
         CommandEncoder {
             context: Arc::clone(&self.context),
             id: Context::device_create_command_encoder(&*self.context, &self.id, desc),
+            device_id: self.id.clone(),
+            zero_buffer: Arc::clone(&self.zero_buffer),
             _p: Default::default(),
         }
     }
@@ -1457,66 +1858,136 @@ impl Device {
 
     /// Creates a new [`BindGroup`].
     pub fn create_bind_group(&self, desc: &BindGroupDescriptor) -> BindGroup {
+        let id = Context::device_create_bind_group(&*self.context, &self.id, desc);
         BindGroup {
-            context: Arc::clone(&self.context),
-            id: Context::device_create_bind_group(&*self.context, &self.id, desc),
+            owner: Owner::new(&self.context, id, |context, id| context.bind_group_drop(id)),
         }
     }
 
     /// Creates a [`BindGroupLayout`].
     pub fn create_bind_group_layout(&self, desc: &BindGroupLayoutDescriptor) -> BindGroupLayout {
+        let id = Context::device_create_bind_group_layout(&*self.context, &self.id, desc);
         BindGroupLayout {
-            context: Arc::clone(&self.context),
-            id: Context::device_create_bind_group_layout(&*self.context, &self.id, desc),
+            owner: Owner::new(&self.context, id, |context, id| {
+                context.bind_group_layout_drop(id)
+            }),
+            entries: Some(desc.entries.into()),
         }
     }
 
     /// Creates a [`PipelineLayout`].
     pub fn create_pipeline_layout(&self, desc: &PipelineLayoutDescriptor) -> PipelineLayout {
+        let id = Context::device_create_pipeline_layout(&*self.context, &self.id, desc);
         PipelineLayout {
-            context: Arc::clone(&self.context),
-            id: Context::device_create_pipeline_layout(&*self.context, &self.id, desc),
+            owner: Owner::new(&self.context, id, |context, id| {
+                context.pipeline_layout_drop(id)
+            }),
         }
     }
 
     /// Creates a [`RenderPipeline`].
     pub fn create_render_pipeline(&self, desc: &RenderPipelineDescriptor) -> RenderPipeline {
-        RenderPipeline {
-            context: Arc::clone(&self.context),
-            id: Context::device_create_render_pipeline(&*self.context, &self.id, desc),
-        }
+        self.try_create_render_pipeline(desc)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Creates a [`RenderPipeline`], returning an error instead of panicking
+    /// if the native backend rejects `desc`.
+    pub(crate) fn try_create_render_pipeline(
+        &self,
+        desc: &RenderPipelineDescriptor,
+    ) -> Result<RenderPipeline, CreatePipelineError> {
+        let id = Context::device_create_render_pipeline(&*self.context, &self.id, desc)?;
+        Ok(RenderPipeline {
+            owner: Owner::new(&self.context, id, |context, id| {
+                context.render_pipeline_drop(id)
+            }),
+        })
     }
 
     /// Creates a [`ComputePipeline`].
     pub fn create_compute_pipeline(&self, desc: &ComputePipelineDescriptor) -> ComputePipeline {
-        ComputePipeline {
-            context: Arc::clone(&self.context),
-            id: Context::device_create_compute_pipeline(&*self.context, &self.id, desc),
-        }
+        self.try_create_compute_pipeline(desc)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Creates a [`ComputePipeline`], returning an error instead of panicking
+    /// if the native backend rejects `desc`.
+    pub(crate) fn try_create_compute_pipeline(
+        &self,
+        desc: &ComputePipelineDescriptor,
+    ) -> Result<ComputePipeline, CreatePipelineError> {
+        let id = Context::device_create_compute_pipeline(&*self.context, &self.id, desc)?;
+        Ok(ComputePipeline {
+            owner: Owner::new(&self.context, id, |context, id| {
+                context.compute_pipeline_drop(id)
+            }),
+        })
+    }
+
+    /// Creates a [`RenderPipeline`] without blocking the calling thread.
+    ///
+    /// On native, the shaders are compiled on a worker thread. On the web, this maps to
+    /// `createRenderPipelineAsync` when the browser supports it.
+    ///
+    /// The future resolves to an error if the backend rejects `desc`, where
+    /// [`Device::create_render_pipeline`] would panic.
+    ///
+    /// On native, the worker thread keeps the shader modules and the layout referenced
+    /// by `desc` alive until it is done. Dropping the future early doesn't wait for it,
+    /// and the pipeline is dropped as soon as it is created.
+    pub fn create_render_pipeline_async(
+        &self,
+        desc: &RenderPipelineDescriptor,
+    ) -> impl Future<Output = Result<RenderPipeline, CreatePipelineError>> + Send {
+        let context = Arc::clone(&self.context);
+        let future =
+            Context::device_create_render_pipeline_async(Arc::clone(&self.context), &self.id, desc);
+        future.map(move |result| {
+            result.map(|id| RenderPipeline {
+                owner: Owner::new(&context, id, |context, id| context.render_pipeline_drop(id)),
+            })
+        })
+    }
+
+    /// Creates a [`ComputePipeline`] without blocking the calling thread.
+    ///
+    /// See [`Device::create_render_pipeline_async`] for details.
+    pub fn create_compute_pipeline_async(
+        &self,
+        desc: &ComputePipelineDescriptor,
+    ) -> impl Future<Output = Result<ComputePipeline, CreatePipelineError>> + Send {
+        let context = Arc::clone(&self.context);
+        let future = Context::device_create_compute_pipeline_async(
+            Arc::clone(&self.context),
+            &self.id,
+            desc,
+        );
+        future.map(move |result| {
+            result.map(|id| ComputePipeline {
+                owner: Owner::new(&context, id, |context, id| {
+                    context.compute_pipeline_drop(id)
+                }),
+            })
+        })
     }
 
     /// Creates a [`Buffer`].
     pub fn create_buffer(&self, desc: &BufferDescriptor) -> Buffer {
-        let mut map_context = MapContext::new(desc.size);
-        if desc.mapped_at_creation {
-            map_context.initial_range = 0..desc.size;
-        }
-        Buffer {
-            context: Arc::clone(&self.context),
-            id: Context::device_create_buffer(&*self.context, &self.id, desc),
-            map_context: Mutex::new(map_context),
-            usage: desc.usage,
-        }
+        Buffer::create(&self.context, &self.id, desc)
     }
 
     /// Creates a new [`Texture`].
     ///
     /// `desc` specifies the general format of the texture.
     pub fn create_texture(&self, desc: &TextureDescriptor) -> Texture {
+        let id = Context::device_create_texture(&*self.context, &self.id, desc);
         Texture {
-            context: Arc::clone(&self.context),
-            id: Context::device_create_texture(&*self.context, &self.id, desc),
-            owned: true,
+            owner: Owner::new(&self.context, id, |context, id| context.texture_drop(id)),
+            descriptor: TextureDescriptor {
+                label: None,
+                ..desc.clone()
+            },
         }
     }
 
@@ -1524,9 +1995,13 @@ impl Device {
     ///
     /// `desc` specifies the behavior of the sampler.
     pub fn create_sampler(&self, desc: &SamplerDescriptor) -> Sampler {
+        let id = Context::device_create_sampler(&*self.context, &self.id, desc);
         Sampler {
-            context: Arc::clone(&self.context),
-            id: Context::device_create_sampler(&*self.context, &self.id, desc),
+            owner: Owner::new(&self.context, id, |context, id| context.sampler_drop(id)),
+            descriptor: SamplerDescriptor {
+                label: None,
+                ..desc.clone()
+            },
         }
     }
 
@@ -1540,6 +2015,7 @@ impl Device {
         SwapChain {
             context: Arc::clone(&self.context),
             id: Context::device_create_swap_chain(&*self.context, &self.id, &surface.id, desc),
+            format: desc.format,
         }
     }
 }
@@ -1576,9 +2052,56 @@ impl Display for BufferAsyncError {
 
 impl Error for BufferAsyncError {}
 
-/// Type of buffer mapping.
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum MapMode {
+/// Error occurred when trying to create a shader module.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CreateShaderModuleError {
+    info: CompilationInfo,
+}
+
+impl CreateShaderModuleError {
+    fn new(message: CompilationMessage) -> Self {
+        CreateShaderModuleError {
+            info: CompilationInfo {
+                messages: vec![message],
+            },
+        }
+    }
+
+    /// Get the messages produced by the failed compilation.
+    pub fn compilation_info(&self) -> &CompilationInfo {
+        &self.info
+    }
+}
+
+impl Display for CreateShaderModuleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Creating a shader module failed")?;
+        for message in self.info.messages.iter() {
+            write!(f, "\n{}", message)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for CreateShaderModuleError {}
+
+/// Error occurred when trying to create a render or compute pipeline.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CreatePipelineError {
+    description: String,
+}
+
+impl Display for CreatePipelineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Creating a pipeline failed: {}", self.description)
+    }
+}
+
+impl Error for CreatePipelineError {}
+
+/// Type of buffer mapping.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MapMode {
     /// Map only for reading
     Read,
     /// Map only for writing
@@ -1590,11 +2113,13 @@ fn range_to_offset_size<S: RangeBounds<BufferAddress>>(
 ) -> (BufferAddress, Option<BufferSize>) {
     let offset = match bounds.start_bound() {
         Bound::Included(&bound) => bound,
-        Bound::Excluded(&bound) => bound + 1,
+        Bound::Excluded(&bound) => bound.checked_add(1).expect("Range start overflows"),
         Bound::Unbounded => 0,
     };
     let size = match bounds.end_bound() {
-        Bound::Included(&bound) => BufferSize::new(bound + 1 - offset),
+        Bound::Included(&bound) => {
+            BufferSize::new(bound.checked_add(1).expect("Range end overflows") - offset)
+        }
         Bound::Excluded(&bound) => BufferSize::new(bound - offset),
         Bound::Unbounded => None,
     };
@@ -1602,6 +2127,27 @@ fn range_to_offset_size<S: RangeBounds<BufferAddress>>(
     (offset, size)
 }
 
+/// Returns the number of whole blocks in each row and column of a mip level,
+/// or `None` if the level is smaller than a block.
+fn copyable_blocks(width: u32, height: u32, block_dimensions: (u32, u32)) -> Option<(u32, u32)> {
+    let (block_width, block_height) = block_dimensions;
+    match (width / block_width, height / block_height) {
+        (0, _) | (_, 0) => None,
+        blocks => Some(blocks),
+    }
+}
+
+#[test]
+fn test_copyable_blocks() {
+    assert_eq!(copyable_blocks(7, 3, (1, 1)), Some((7, 3)));
+    assert_eq!(copyable_blocks(16, 8, (4, 4)), Some((4, 2)));
+    // Partial blocks at the edges are left out.
+    assert_eq!(copyable_blocks(10, 6, (4, 4)), Some((2, 1)));
+    // Mip levels smaller than a block have nothing to copy to.
+    assert_eq!(copyable_blocks(2, 2, (4, 4)), None);
+    assert_eq!(copyable_blocks(8, 1, (4, 4)), None);
+}
+
 /// Read only view into a mapped buffer.
 #[derive(Debug)]
 pub struct BufferView<'a> {
@@ -1632,7 +2178,7 @@ impl std::ops::Deref for BufferViewMut<'_> {
         assert!(
             self.readable,
             "Attempting to read a write-only mapping for buffer {:?}",
-            self.slice.buffer.id
+            self.slice.buffer.owner.id
         );
         self.data
     }
@@ -1665,6 +2211,41 @@ impl Drop for BufferViewMut<'_> {
 }
 
 impl Buffer {
+    fn create(
+        context: &Arc<C>,
+        device: &<C as Context>::DeviceId,
+        desc: &BufferDescriptor,
+    ) -> Self {
+        let mut map_context = MapContext::new(desc.size);
+        if desc.mapped_at_creation {
+            map_context.initial_range = 0..desc.size;
+        }
+        let id = Context::device_create_buffer(&**context, device, desc);
+        Buffer {
+            owner: Owner::new(context, id, |context, id| context.buffer_drop(id)),
+            map_context: Arc::new(Mutex::new(map_context)),
+            descriptor: BufferDescriptor {
+                label: None,
+                ..desc.clone()
+            },
+        }
+    }
+
+    /// Returns the descriptor the buffer was created with, without its label.
+    pub fn descriptor(&self) -> &BufferDescriptor<'static> {
+        &self.descriptor
+    }
+
+    /// Returns the size of the buffer.
+    pub fn size(&self) -> BufferAddress {
+        self.descriptor.size
+    }
+
+    /// Returns the allowed usages of the buffer.
+    pub fn usage(&self) -> BufferUsage {
+        self.descriptor.usage
+    }
+
     /// Return the binding view of the entire buffer.
     pub fn as_entire_binding(&self) -> BindingResource {
         BindingResource::Buffer {
@@ -1676,8 +2257,19 @@ impl Buffer {
 
     /// Use only a portion of this Buffer for a given operation. Choosing a range with no end
     /// will use the rest of the buffer. Using a totally unbounded range will use the entire buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range goes past the end of the buffer.
     pub fn slice<S: RangeBounds<BufferAddress>>(&self, bounds: S) -> BufferSlice {
         let (offset, size) = range_to_offset_size(bounds);
+        let end = size.map_or(offset, |size| offset + size.get());
+        assert!(
+            end <= self.size(),
+            "Range {:?} is out of bounds of a buffer of size {}",
+            offset..end,
+            self.size()
+        );
         BufferSlice {
             buffer: self,
             offset,
@@ -1688,12 +2280,95 @@ impl Buffer {
     /// Flushes any pending write operations and unmaps the buffer from host memory.
     pub fn unmap(&self) {
         self.map_context.lock().reset();
-        Context::buffer_unmap(&*self.context, &self.id);
+        Context::buffer_unmap(&*self.owner.context, self.valid_id());
+    }
+
+    /// Destroys the buffer, releasing its memory without waiting for all of its handles
+    /// to be dropped.
+    ///
+    /// The memory is released as soon as the GPU is done with the commands already submitted.
+    /// Afterwards, using the buffer through any of its handles panics.
+    /// Destroying a buffer more than once has no effect.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer still has accessible mapped views.
+    pub fn destroy(&self) {
+        let mut map_context = self.map_context.lock();
+        assert!(
+            map_context.sub_ranges.is_empty(),
+            "You cannot destroy a buffer that still has accessible mapped views"
+        );
+        map_context.reset();
+        self.owner
+            .destroy(|context, id| Context::buffer_destroy(context, id));
+    }
+
+    /// Returns `true` if the buffer was destroyed with [`Buffer::destroy`].
+    pub fn is_destroyed(&self) -> bool {
+        self.owner.is_destroyed()
+    }
+
+    fn valid_id(&self) -> &<C as Context>::BufferId {
+        assert!(
+            !self.is_destroyed(),
+            "Buffer {:?} is used after being destroyed",
+            self.owner.id
+        );
+        &self.owner.id
     }
 }
 
 impl<'a> BufferSlice<'a> {
-    //TODO: fn slice(&self) -> Self
+    /// Use only a portion of this slice. The bounds are relative to the start of this slice,
+    /// and work like those of [`Buffer::slice`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is empty or goes past the end of this slice.
+    pub fn slice<S: RangeBounds<BufferAddress>>(&self, bounds: S) -> Self {
+        let parent_size = self.size();
+        let start = match bounds.start_bound() {
+            Bound::Included(&bound) => bound,
+            Bound::Excluded(&bound) => bound.checked_add(1).expect("Range start overflows"),
+            Bound::Unbounded => 0,
+        };
+        let end = match bounds.end_bound() {
+            Bound::Included(&bound) => bound.checked_add(1).expect("Range end overflows"),
+            Bound::Excluded(&bound) => bound,
+            Bound::Unbounded => parent_size,
+        };
+        assert!(
+            start < end && end <= parent_size,
+            "Range {:?} is empty or out of bounds of a slice of size {}",
+            start..end,
+            parent_size
+        );
+
+        BufferSlice {
+            buffer: self.buffer,
+            offset: self.offset + start,
+            size: BufferSize::new(end - start),
+        }
+    }
+
+    /// Returns the offset of this slice in its buffer.
+    pub fn offset(&self) -> BufferAddress {
+        self.offset
+    }
+
+    /// Returns the size of this slice.
+    pub fn size(&self) -> BufferAddress {
+        match self.size {
+            Some(size) => size.get(),
+            None => self.buffer.map_context.lock().total_size - self.offset,
+        }
+    }
+
+    /// Returns the buffer this slice is part of.
+    pub fn buffer(&self) -> &'a Buffer {
+        self.buffer
+    }
 
     /// Map the buffer. Buffer is ready to map once the future is resolved.
     ///
@@ -1713,7 +2388,7 @@ impl<'a> BufferSlice<'a> {
                 mc.initial_range,
                 0..0,
                 "Buffer {:?} is already mapped",
-                self.buffer.id
+                self.buffer.owner.id
             );
             let end = match self.size {
                 Some(s) => self.offset + s.get(),
@@ -1723,8 +2398,8 @@ impl<'a> BufferSlice<'a> {
             end
         };
         Context::buffer_map_async(
-            &*self.buffer.context,
-            &self.buffer.id,
+            &*self.buffer.owner.context,
+            self.buffer.valid_id(),
             mode,
             self.offset..end,
         )
@@ -1735,8 +2410,8 @@ impl<'a> BufferSlice<'a> {
     pub fn get_mapped_range(&self) -> BufferView<'a> {
         let end = self.buffer.map_context.lock().add(self.offset, self.size);
         let data = Context::buffer_get_mapped_range(
-            &*self.buffer.context,
-            &self.buffer.id,
+            &*self.buffer.owner.context,
+            self.buffer.valid_id(),
             self.offset..end,
         );
         BufferView { slice: *self, data }
@@ -1747,50 +2422,169 @@ impl<'a> BufferSlice<'a> {
     pub fn get_mapped_range_mut(&self) -> BufferViewMut<'a> {
         let end = self.buffer.map_context.lock().add(self.offset, self.size);
         let data = Context::buffer_get_mapped_range_mut(
-            &*self.buffer.context,
-            &self.buffer.id,
+            &*self.buffer.owner.context,
+            self.buffer.valid_id(),
             self.offset..end,
         );
         BufferViewMut {
             slice: *self,
             data,
-            readable: self.buffer.usage.contains(BufferUsage::MAP_READ),
+            readable: self.buffer.usage().contains(BufferUsage::MAP_READ),
         }
     }
 }
 
-impl Drop for Buffer {
-    fn drop(&mut self) {
-        if !thread::panicking() {
-            self.context.buffer_drop(&self.id);
-        }
+impl Texture {
+    /// Destroys the texture, releasing its memory without waiting for all of its handles
+    /// to be dropped.
+    ///
+    /// The memory is released as soon as the GPU is done with the commands already submitted.
+    /// Afterwards, using the texture through any of its handles panics.
+    /// Destroying a texture more than once has no effect.
+    ///
+    /// Views of the texture must not be used either once it is destroyed. On native, they keep
+    /// its memory alive until they are dropped.
+    pub fn destroy(&self) {
+        self.owner
+            .destroy(|context, id| Context::texture_destroy(context, id));
+    }
+
+    /// Returns `true` if the texture was destroyed with [`Texture::destroy`].
+    pub fn is_destroyed(&self) -> bool {
+        self.owner.is_destroyed()
+    }
+
+    fn valid_id(&self) -> &<C as Context>::TextureId {
+        assert!(
+            !self.is_destroyed(),
+            "Texture {:?} is used after being destroyed",
+            self.owner.id
+        );
+        &self.owner.id
     }
-}
 
-impl Texture {
     /// Creates a view of this texture.
     pub fn create_view(&self, desc: &TextureViewDescriptor) -> TextureView {
+        let texture = &self.descriptor;
+        let dimension = desc.dimension.unwrap_or(match texture.dimension {
+            TextureDimension::D1 => TextureViewDimension::D1,
+            TextureDimension::D2 if texture.size.depth > 1 => TextureViewDimension::D2Array,
+            TextureDimension::D2 => TextureViewDimension::D2,
+            TextureDimension::D3 => TextureViewDimension::D3,
+        });
+        let layer_count = match texture.dimension {
+            TextureDimension::D2 => texture.size.depth,
+            _ => 1,
+        };
+        let descriptor = TextureViewDescriptor {
+            label: None,
+            format: Some(desc.format.unwrap_or(texture.format)),
+            dimension: Some(dimension),
+            aspect: desc.aspect,
+            base_mip_level: desc.base_mip_level,
+            level_count: desc.level_count.or_else(|| {
+                NonZeroU32::new(texture.mip_level_count.saturating_sub(desc.base_mip_level))
+            }),
+            base_array_layer: desc.base_array_layer,
+            array_layer_count: desc
+                .array_layer_count
+                .or_else(|| NonZeroU32::new(layer_count.saturating_sub(desc.base_array_layer))),
+        };
+
+        let id = Context::texture_create_view(&*self.owner.context, self.valid_id(), desc);
         TextureView {
-            context: Arc::clone(&self.context),
-            id: Context::texture_create_view(&*self.context, &self.id, desc),
-            owned: true,
+            owner: Owner::new(&self.owner.context, id, |context, id| {
+                context.texture_view_drop(id)
+            }),
+            descriptor,
         }
     }
+
+    /// Returns the descriptor the texture was created with, without its label.
+    pub fn descriptor(&self) -> &TextureDescriptor<'static> {
+        &self.descriptor
+    }
+
+    /// Returns the size of the texture.
+    pub fn size(&self) -> Extent3d {
+        self.descriptor.size
+    }
+
+    /// Returns the number of mip levels of the texture.
+    pub fn mip_level_count(&self) -> u32 {
+        self.descriptor.mip_level_count
+    }
+
+    /// Returns the number of samples per texel of the texture.
+    pub fn sample_count(&self) -> u32 {
+        self.descriptor.sample_count
+    }
+
+    /// Returns the dimension of the texture.
+    pub fn dimension(&self) -> TextureDimension {
+        self.descriptor.dimension
+    }
+
+    /// Returns the format of the texture.
+    pub fn format(&self) -> TextureFormat {
+        self.descriptor.format
+    }
+
+    /// Returns the allowed usages of the texture.
+    pub fn usage(&self) -> TextureUsage {
+        self.descriptor.usage
+    }
 }
 
-impl Drop for Texture {
-    fn drop(&mut self) {
-        if self.owned && !thread::panicking() {
-            self.context.texture_drop(&self.id);
-        }
+impl TextureView {
+    /// Returns the descriptor the view was created with, without its label.
+    ///
+    /// Fields that were `None` at creation are set to the values they defaulted to.
+    pub fn descriptor(&self) -> &TextureViewDescriptor<'static> {
+        &self.descriptor
+    }
+
+    /// Returns the format of the view.
+    pub fn format(&self) -> TextureFormat {
+        self.descriptor.format.unwrap()
+    }
+
+    /// Returns the dimension of the view.
+    pub fn dimension(&self) -> TextureViewDimension {
+        self.descriptor.dimension.unwrap()
+    }
+
+    /// Returns the aspect of the texture seen by the view.
+    pub fn aspect(&self) -> TextureAspect {
+        self.descriptor.aspect
+    }
+
+    /// Returns the mip levels seen by the view.
+    pub fn mip_levels(&self) -> Range<u32> {
+        let count = self.descriptor.level_count.map_or(0, NonZeroU32::get);
+        self.descriptor.base_mip_level..self.descriptor.base_mip_level + count
+    }
+
+    /// Returns the array layers seen by the view.
+    pub fn array_layers(&self) -> Range<u32> {
+        let count = self.descriptor.array_layer_count.map_or(0, NonZeroU32::get);
+        self.descriptor.base_array_layer..self.descriptor.base_array_layer + count
+    }
+
+    fn valid_id(&self) -> &<C as Context>::TextureViewId {
+        assert!(
+            !self.owner.is_expired(),
+            "Texture view {:?} of a swap chain frame is used after the frame was presented",
+            self.owner.id
+        );
+        &self.owner.id
     }
 }
 
-impl Drop for TextureView {
-    fn drop(&mut self) {
-        if self.owned && !thread::panicking() {
-            self.context.texture_view_drop(&self.id);
-        }
+impl Sampler {
+    /// Returns the descriptor the sampler was created with, without its label.
+    pub fn descriptor(&self) -> &SamplerDescriptor<'static> {
+        &self.descriptor
     }
 }
 
@@ -1813,15 +2607,18 @@ impl CommandEncoder {
         RenderPass {
             id: Context::command_encoder_begin_render_pass(&*self.context, &self.id, desc),
             parent: self,
+            owned_buffers: Vec::new(),
+            owned_bind_groups: Vec::new(),
+            owned_pipelines: Vec::new(),
         }
     }
 
     /// Begins recording of a compute pass.
     ///
     /// This function returns a [`ComputePass`] object which records a single compute pass.
-    pub fn begin_compute_pass(&mut self) -> ComputePass {
+    pub fn begin_compute_pass(&mut self, desc: &ComputePassDescriptor) -> ComputePass {
         ComputePass {
-            id: Context::command_encoder_begin_compute_pass(&*self.context, &self.id),
+            id: Context::command_encoder_begin_compute_pass(&*self.context, &self.id, desc),
             parent: self,
         }
     }
@@ -1843,9 +2640,9 @@ impl CommandEncoder {
         Context::command_encoder_copy_buffer_to_buffer(
             &*self.context,
             &self.id,
-            &source.id,
+            source.valid_id(),
             source_offset,
-            &destination.id,
+            destination.valid_id(),
             destination_offset,
             copy_size,
         );
@@ -1917,6 +2714,234 @@ impl CommandEncoder {
         );
     }
 
+    /// Returns the zero-filled buffer of the device, growing it to at least `size` bytes
+    /// if it is smaller, up to [`ZERO_BUFFER_MAX_SIZE`].
+    fn zero_buffer(&self, size: BufferAddress) -> Buffer {
+        let size = size.min(ZERO_BUFFER_MAX_SIZE);
+        let mut zero_buffer = self.zero_buffer.lock();
+        if let Some(ref buffer) = *zero_buffer {
+            if buffer.size() >= size {
+                return buffer.clone();
+            }
+        }
+
+        let buffer = Buffer::create(
+            &self.context,
+            &self.device_id,
+            &BufferDescriptor {
+                label: Some("zero buffer"),
+                size: size.next_power_of_two().min(ZERO_BUFFER_MAX_SIZE),
+                usage: BufferUsage::COPY_SRC,
+                mapped_at_creation: true,
+            },
+        );
+        for byte in buffer.slice(..).get_mapped_range_mut().iter_mut() {
+            *byte = 0;
+        }
+        buffer.unmap();
+        *zero_buffer = Some(buffer.clone());
+        buffer
+    }
+
+    /// Clears a range of a buffer to zero.
+    ///
+    /// There is no clear command in the native backend, so the range is overwritten by copies
+    /// from a zero-filled buffer kept by the device, which makes clearing cheap enough to do
+    /// every frame. The buffer must have the [`BufferUsage::COPY_DST`] usage.
+    ///
+    /// # Panics
+    ///
+    /// - Start or end of the range not a multiple of [`COPY_BUFFER_ALIGNMENT`].
+    /// - Range would overrun buffer.
+    pub fn clear_buffer<S: RangeBounds<BufferAddress>>(&mut self, buffer: &Buffer, bounds: S) {
+        let (offset, size) = range_to_offset_size(bounds);
+        let end = size.map_or(buffer.size(), |size| offset + size.get());
+        assert!(
+            offset % COPY_BUFFER_ALIGNMENT == 0 && end % COPY_BUFFER_ALIGNMENT == 0,
+            "Cleared range {:?} is not aligned to COPY_BUFFER_ALIGNMENT",
+            offset..end
+        );
+        assert!(
+            end <= buffer.size(),
+            "Cleared range {:?} overruns the buffer of {} bytes",
+            offset..end,
+            buffer.size()
+        );
+        if offset == end {
+            return;
+        }
+
+        let zero_buffer = self.zero_buffer(end - offset);
+        let mut offset = offset;
+        while offset < end {
+            let size = (end - offset).min(zero_buffer.size());
+            self.copy_buffer_to_buffer(&zero_buffer, 0, buffer, offset, size);
+            offset += size;
+        }
+    }
+
+    /// Clears a range of subresources of a texture to zero.
+    ///
+    /// There is no clear command in the native backend, so 2D textures with the
+    /// [`TextureUsage::OUTPUT_ATTACHMENT`] usage are cleared with an empty render pass per
+    /// subresource. Other textures are cleared with copies from a zero-filled buffer kept by
+    /// the device, which requires the [`TextureUsage::COPY_DST`] usage.
+    ///
+    /// Copies can only write whole blocks of block-compressed formats, so the mip levels
+    /// smaller than a block, and the partial blocks at the edges of the other ones,
+    /// are left as is.
+    ///
+    /// # Panics
+    ///
+    /// - Range end overflows a `u32`, or would overrun texture.
+    /// - Texture has a depth format, but isn't a 2D texture with the
+    ///   [`TextureUsage::OUTPUT_ATTACHMENT`] usage.
+    pub fn clear_texture(&mut self, texture: &Texture, range: &ImageSubresourceRange) {
+        let desc = texture.descriptor();
+        let mip_levels = range.base_mip_level
+            ..range.mip_level_count.map_or(desc.mip_level_count, |count| {
+                range
+                    .base_mip_level
+                    .checked_add(count.get())
+                    .expect("Mip level range end overflows")
+            });
+        let layers = range.base_array_layer
+            ..range.array_layer_count.map_or(desc.size.depth, |count| {
+                range
+                    .base_array_layer
+                    .checked_add(count.get())
+                    .expect("Array layer range end overflows")
+            });
+        assert!(
+            mip_levels.end <= desc.mip_level_count && layers.end <= desc.size.depth,
+            "Cleared range {:?} overruns the texture",
+            range
+        );
+
+        let info = format::format_info(desc.format);
+        if desc.usage.contains(TextureUsage::OUTPUT_ATTACHMENT)
+            && desc.dimension == TextureDimension::D2
+        {
+            let clears_stencil = info.has_stencil && range.aspect != TextureAspect::DepthOnly;
+            let clears_depth = range.aspect != TextureAspect::StencilOnly;
+            for mip_level in mip_levels {
+                for layer in layers.clone() {
+                    let view = texture.create_view(&TextureViewDescriptor {
+                        label: Some("clear_texture"),
+                        format: None,
+                        dimension: Some(TextureViewDimension::D2),
+                        // The cleared aspects are selected by the operations of the attachment.
+                        aspect: TextureAspect::All,
+                        base_mip_level: mip_level,
+                        level_count: NonZeroU32::new(1),
+                        base_array_layer: layer,
+                        array_layer_count: NonZeroU32::new(1),
+                    });
+                    let color_attachment = RenderPassColorAttachmentDescriptor {
+                        attachment: &view,
+                        resolve_target: None,
+                        ops: Operations {
+                            load: LoadOp::Clear(Color::TRANSPARENT),
+                            store: true,
+                        },
+                    };
+                    let depth_stencil_attachment = RenderPassDepthStencilAttachmentDescriptor {
+                        attachment: &view,
+                        depth_ops: if clears_depth {
+                            Some(Operations {
+                                load: LoadOp::Clear(0.0),
+                                store: true,
+                            })
+                        } else {
+                            None
+                        },
+                        stencil_ops: if clears_stencil {
+                            Some(Operations {
+                                load: LoadOp::Clear(0),
+                                store: true,
+                            })
+                        } else {
+                            None
+                        },
+                    };
+                    let (color_attachments, depth_stencil_attachment) = if info.has_depth {
+                        (Vec::new(), Some(depth_stencil_attachment))
+                    } else {
+                        (vec![color_attachment], None)
+                    };
+                    self.begin_render_pass(&RenderPassDescriptor {
+                        label: None,
+                        color_attachments: &color_attachments,
+                        depth_stencil_attachment,
+                    });
+                }
+            }
+            return;
+        }
+
+        assert!(
+            !info.has_depth,
+            "Depth textures can only be cleared if they are 2D and have the OUTPUT_ATTACHMENT usage"
+        );
+        let (block_width, block_height) = info.block_dimensions;
+        for mip_level in mip_levels {
+            let width = (desc.size.width >> mip_level).max(1);
+            let height = (desc.size.height >> mip_level).max(1);
+            let (width_blocks, height_blocks) =
+                match copyable_blocks(width, height, info.block_dimensions) {
+                    Some(blocks) => blocks,
+                    None => continue,
+                };
+            let bytes_per_row = (width_blocks * info.block_size + COPY_BYTES_PER_ROW_ALIGNMENT - 1)
+                / COPY_BYTES_PER_ROW_ALIGNMENT
+                * COPY_BYTES_PER_ROW_ALIGNMENT;
+            // 3D textures have fewer depth slices in each mip level, clear the existing ones.
+            let layers = match desc.dimension {
+                TextureDimension::D3 => {
+                    layers.start..layers.end.min((desc.size.depth >> mip_level).max(1))
+                }
+                _ => layers.clone(),
+            };
+
+            let zero_buffer =
+                self.zero_buffer(bytes_per_row as BufferAddress * height_blocks as BufferAddress);
+            let rows_per_copy = ((zero_buffer.size() / bytes_per_row as BufferAddress) as u32)
+                .min(height_blocks)
+                .max(1);
+            for layer in layers {
+                let mut row = 0;
+                while row < height_blocks {
+                    let rows = rows_per_copy.min(height_blocks - row);
+                    self.copy_buffer_to_texture(
+                        BufferCopyView {
+                            buffer: &zero_buffer,
+                            layout: TextureDataLayout {
+                                offset: 0,
+                                bytes_per_row,
+                                rows_per_image: rows * block_height,
+                            },
+                        },
+                        TextureCopyView {
+                            texture,
+                            mip_level,
+                            origin: Origin3d {
+                                x: 0,
+                                y: row * block_height,
+                                z: layer,
+                            },
+                        },
+                        Extent3d {
+                            width: width_blocks * block_width,
+                            height: rows * block_height,
+                            depth: 1,
+                        },
+                    );
+                    row += rows;
+                }
+            }
+        }
+    }
+
     /// Inserts debug marker.
     pub fn insert_debug_marker(&mut self, label: &str) {
         Context::command_encoder_insert_debug_marker(&*self.context, &self.id, label);
@@ -1931,6 +2956,14 @@ impl CommandEncoder {
     pub fn pop_debug_group(&mut self) {
         Context::command_encoder_pop_debug_group(&*self.context, &self.id);
     }
+
+    /// Pushes a debug group, which is popped when the returned guard is dropped.
+    ///
+    /// Commands are recorded into the group through the guard.
+    pub fn debug_group(&mut self, label: &str) -> DebugGroup<'_, Self> {
+        self.push_debug_group(label);
+        DebugGroup { target: self }
+    }
 }
 
 impl<'a> RenderPass<'a> {
@@ -1944,14 +2977,14 @@ impl<'a> RenderPass<'a> {
         bind_group: &'a BindGroup,
         offsets: &[DynamicOffset],
     ) {
-        RenderInner::set_bind_group(&mut self.id, index, &bind_group.id, offsets)
+        RenderInner::set_bind_group(&mut self.id, index, &bind_group.owner.id, offsets)
     }
 
     /// Sets the active render pipeline.
     ///
     /// Subsequent draw calls will exhibit the behavior defined by `pipeline`.
     pub fn set_pipeline(&mut self, pipeline: &'a RenderPipeline) {
-        RenderInner::set_pipeline(&mut self.id, &pipeline.id)
+        RenderInner::set_pipeline(&mut self.id, &pipeline.owner.id)
     }
 
     /// Sets the blend color as used by some of the blending modes.
@@ -1968,7 +3001,7 @@ impl<'a> RenderPass<'a> {
     pub fn set_index_buffer(&mut self, buffer_slice: BufferSlice<'a>) {
         RenderInner::set_index_buffer(
             &mut self.id,
-            &buffer_slice.buffer.id,
+            buffer_slice.buffer.valid_id(),
             buffer_slice.offset,
             buffer_slice.size,
         )
@@ -1988,7 +3021,7 @@ impl<'a> RenderPass<'a> {
         RenderInner::set_vertex_buffer(
             &mut self.id,
             slot,
-            &buffer_slice.buffer.id,
+            buffer_slice.buffer.valid_id(),
             buffer_slice.offset,
             buffer_slice.size,
         )
@@ -2037,6 +3070,14 @@ impl<'a> RenderPass<'a> {
         self.id.pop_debug_group();
     }
 
+    /// Pushes a debug group, which is popped when the returned guard is dropped.
+    ///
+    /// Commands are recorded into the group through the guard.
+    pub fn debug_group(&mut self, label: &str) -> DebugGroup<'_, Self> {
+        self.push_debug_group(label);
+        DebugGroup { target: self }
+    }
+
     /// Draws indexed primitives using the active index buffer and the active vertex buffers.
     ///
     /// The active index buffer can be set with [`RenderPass::set_index_buffer`], while the active
@@ -2061,7 +3102,8 @@ impl<'a> RenderPass<'a> {
     /// }
     /// ```
     pub fn draw_indirect(&mut self, indirect_buffer: &'a Buffer, indirect_offset: BufferAddress) {
-        self.id.draw_indirect(&indirect_buffer.id, indirect_offset);
+        self.id
+            .draw_indirect(indirect_buffer.valid_id(), indirect_offset);
     }
 
     /// Draws indexed primitives using the active index buffer and the active vertex buffers,
@@ -2088,14 +3130,100 @@ impl<'a> RenderPass<'a> {
         indirect_offset: BufferAddress,
     ) {
         self.id
-            .draw_indexed_indirect(&indirect_buffer.id, indirect_offset);
+            .draw_indexed_indirect(indirect_buffer.valid_id(), indirect_offset);
     }
 
     /// Execute a [render bundle][RenderBundle], which is a set of pre-recorded commands
     /// that can be run together.
     pub fn execute_bundles<I: Iterator<Item = &'a RenderBundle>>(&mut self, render_bundles: I) {
         self.id
-            .execute_bundles(render_bundles.into_iter().map(|rb| &rb.id))
+            .execute_bundles(render_bundles.into_iter().map(|rb| &rb.owner.id))
+    }
+}
+
+/// Variants of the recording functions taking ownership of the resources.
+///
+/// Instead of borrowing the resources for the lifetime of the pass, these functions take a
+/// handle to them, usually obtained by cloning, and the pass keeps it alive until it ends.
+/// This makes it possible to record with resources created on the fly, like temporary bind
+/// groups, without storing them somewhere that outlives the pass.
+///
+/// This comes at a cost: cloning a handle increments an atomic reference count, which is
+/// decremented again when the pass ends, and each call pushes the handle into a vector that
+/// may have to grow. The borrowing functions have none of this overhead, so they should be
+/// preferred for resources that already outlive the pass.
+impl<'a> RenderPass<'a> {
+    /// Sets the active bind group for a given bind group index, keeping it alive until the
+    /// pass ends.
+    ///
+    /// See [`RenderPass::set_bind_group`].
+    pub fn set_bind_group_owned(
+        &mut self,
+        index: u32,
+        bind_group: BindGroup,
+        offsets: &[DynamicOffset],
+    ) {
+        RenderInner::set_bind_group(&mut self.id, index, &bind_group.owner.id, offsets);
+        self.owned_bind_groups.push(bind_group);
+    }
+
+    /// Sets the active render pipeline, keeping it alive until the pass ends.
+    ///
+    /// See [`RenderPass::set_pipeline`].
+    pub fn set_pipeline_owned(&mut self, pipeline: RenderPipeline) {
+        RenderInner::set_pipeline(&mut self.id, &pipeline.owner.id);
+        self.owned_pipelines.push(pipeline);
+    }
+
+    /// Sets a range of `buffer` as the active index buffer, keeping it alive until the pass ends.
+    ///
+    /// See [`RenderPass::set_index_buffer`].
+    pub fn set_index_buffer_owned<S: RangeBounds<BufferAddress>>(
+        &mut self,
+        buffer: Buffer,
+        bounds: S,
+    ) {
+        let (offset, size) = range_to_offset_size(bounds);
+        RenderInner::set_index_buffer(&mut self.id, buffer.valid_id(), offset, size);
+        self.owned_buffers.push(buffer);
+    }
+
+    /// Assigns a range of `buffer` to a vertex buffer slot, keeping it alive until the pass ends.
+    ///
+    /// See [`RenderPass::set_vertex_buffer`].
+    pub fn set_vertex_buffer_owned<S: RangeBounds<BufferAddress>>(
+        &mut self,
+        slot: u32,
+        buffer: Buffer,
+        bounds: S,
+    ) {
+        let (offset, size) = range_to_offset_size(bounds);
+        RenderInner::set_vertex_buffer(&mut self.id, slot, buffer.valid_id(), offset, size);
+        self.owned_buffers.push(buffer);
+    }
+
+    /// Draws primitives based on the contents of `indirect_buffer`, keeping it alive until
+    /// the pass ends.
+    ///
+    /// See [`RenderPass::draw_indirect`].
+    pub fn draw_indirect_owned(&mut self, indirect_buffer: Buffer, indirect_offset: BufferAddress) {
+        self.id
+            .draw_indirect(indirect_buffer.valid_id(), indirect_offset);
+        self.owned_buffers.push(indirect_buffer);
+    }
+
+    /// Draws indexed primitives based on the contents of `indirect_buffer`, keeping it alive
+    /// until the pass ends.
+    ///
+    /// See [`RenderPass::draw_indexed_indirect`].
+    pub fn draw_indexed_indirect_owned(
+        &mut self,
+        indirect_buffer: Buffer,
+        indirect_offset: BufferAddress,
+    ) {
+        self.id
+            .draw_indexed_indirect(indirect_buffer.valid_id(), indirect_offset);
+        self.owned_buffers.push(indirect_buffer);
     }
 }
 
@@ -2126,7 +3254,7 @@ impl<'a> RenderPass<'a> {
         count: u32,
     ) {
         self.id
-            .multi_draw_indirect(&indirect_buffer.id, indirect_offset, count);
+            .multi_draw_indirect(indirect_buffer.valid_id(), indirect_offset, count);
     }
 
     /// Disptaches multiple draw calls from the active index buffer and the active vertex buffers,
@@ -2156,7 +3284,7 @@ impl<'a> RenderPass<'a> {
         count: u32,
     ) {
         self.id
-            .multi_draw_indexed_indirect(&indirect_buffer.id, indirect_offset, count);
+            .multi_draw_indexed_indirect(indirect_buffer.valid_id(), indirect_offset, count);
     }
 }
 
@@ -2201,9 +3329,9 @@ impl<'a> RenderPass<'a> {
         max_count: u32,
     ) {
         self.id.multi_draw_indirect_count(
-            &indirect_buffer.id,
+            indirect_buffer.valid_id(),
             indirect_offset,
-            &count_buffer.id,
+            count_buffer.valid_id(),
             count_offset,
             max_count,
         );
@@ -2250,9 +3378,9 @@ impl<'a> RenderPass<'a> {
         max_count: u32,
     ) {
         self.id.multi_draw_indexed_indirect_count(
-            &indirect_buffer.id,
+            indirect_buffer.valid_id(),
             indirect_offset,
-            &count_buffer.id,
+            count_buffer.valid_id(),
             count_offset,
             max_count,
         );
@@ -2296,6 +3424,7 @@ impl<'a> RenderPass<'a> {
 
 impl<'a> Drop for RenderPass<'a> {
     fn drop(&mut self) {
+        // The owned resources are dropped after this, once the pass has ended.
         if !thread::panicking() {
             self.parent
                 .context
@@ -2315,12 +3444,12 @@ impl<'a> ComputePass<'a> {
         bind_group: &'a BindGroup,
         offsets: &[DynamicOffset],
     ) {
-        ComputePassInner::set_bind_group(&mut self.id, index, &bind_group.id, offsets);
+        ComputePassInner::set_bind_group(&mut self.id, index, &bind_group.owner.id, offsets);
     }
 
     /// Sets the active compute pipeline.
     pub fn set_pipeline(&mut self, pipeline: &'a ComputePipeline) {
-        ComputePassInner::set_pipeline(&mut self.id, &pipeline.id);
+        ComputePassInner::set_pipeline(&mut self.id, &pipeline.owner.id);
     }
 
     /// Inserts debug marker.
@@ -2338,6 +3467,14 @@ impl<'a> ComputePass<'a> {
         self.id.pop_debug_group();
     }
 
+    /// Pushes a debug group, which is popped when the returned guard is dropped.
+    ///
+    /// Commands are recorded into the group through the guard.
+    pub fn debug_group(&mut self, label: &str) -> DebugGroup<'_, Self> {
+        self.push_debug_group(label);
+        DebugGroup { target: self }
+    }
+
     /// Dispatches compute work operations.
     ///
     /// `x`, `y` and `z` denote the number of work groups to dispatch in each dimension.
@@ -2351,7 +3488,11 @@ impl<'a> ComputePass<'a> {
         indirect_buffer: &'a Buffer,
         indirect_offset: BufferAddress,
     ) {
-        ComputePassInner::dispatch_indirect(&mut self.id, &indirect_buffer.id, indirect_offset);
+        ComputePassInner::dispatch_indirect(
+            &mut self.id,
+            indirect_buffer.valid_id(),
+            indirect_offset,
+        );
     }
 }
 
@@ -2379,12 +3520,78 @@ impl<'a> Drop for ComputePass<'a> {
     }
 }
 
+mod debug_group {
+    /// Encoder or pass which debug groups can be pushed to.
+    pub trait Target {
+        fn pop(&mut self);
+    }
+
+    impl Target for super::CommandEncoder {
+        fn pop(&mut self) {
+            self.pop_debug_group();
+        }
+    }
+
+    impl Target for super::RenderPass<'_> {
+        fn pop(&mut self) {
+            self.pop_debug_group();
+        }
+    }
+
+    impl Target for super::ComputePass<'_> {
+        fn pop(&mut self) {
+            self.pop_debug_group();
+        }
+    }
+}
+
+/// Guard of a debug group, which pops it when dropped.
+///
+/// Created by [`CommandEncoder::debug_group`], [`RenderPass::debug_group`] and
+/// [`ComputePass::debug_group`]. The guard dereferences to the encoder or pass, so that
+/// commands are recorded through it:
+///
+/// ```no_run
+/// # fn draw_shadows(pass: &mut wgpu::RenderPass) {
+/// let mut group = pass.debug_group("shadows");
+/// group.draw(0..3, 0..1);
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct DebugGroup<'p, T: debug_group::Target> {
+    target: &'p mut T,
+}
+
+impl<T: debug_group::Target> std::ops::Deref for DebugGroup<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.target
+    }
+}
+
+impl<T: debug_group::Target> std::ops::DerefMut for DebugGroup<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.target
+    }
+}
+
+impl<T: debug_group::Target> Drop for DebugGroup<'_, T> {
+    fn drop(&mut self) {
+        if !thread::panicking() {
+            self.target.pop();
+        }
+    }
+}
+
 impl<'a> RenderBundleEncoder<'a> {
     /// Finishes recording and returns a [`RenderBundle`] that can be executed in other render passes.
     pub fn finish(self, desc: &RenderBundleDescriptor) -> RenderBundle {
+        let id = Context::render_bundle_encoder_finish(&*self.context, self.id, desc);
         RenderBundle {
-            context: Arc::clone(&self.context),
-            id: Context::render_bundle_encoder_finish(&*self.context, self.id, desc),
+            owner: Owner::new(&self.context, id, |context, id| {
+                context.render_bundle_drop(id)
+            }),
         }
     }
 
@@ -2398,14 +3605,14 @@ impl<'a> RenderBundleEncoder<'a> {
         bind_group: &'a BindGroup,
         offsets: &[DynamicOffset],
     ) {
-        RenderInner::set_bind_group(&mut self.id, index, &bind_group.id, offsets)
+        RenderInner::set_bind_group(&mut self.id, index, &bind_group.owner.id, offsets)
     }
 
     /// Sets the active render pipeline.
     ///
     /// Subsequent draw calls will exhibit the behavior defined by `pipeline`.
     pub fn set_pipeline(&mut self, pipeline: &'a RenderPipeline) {
-        RenderInner::set_pipeline(&mut self.id, &pipeline.id)
+        RenderInner::set_pipeline(&mut self.id, &pipeline.owner.id)
     }
 
     /// Sets the active index buffer.
@@ -2415,7 +3622,7 @@ impl<'a> RenderBundleEncoder<'a> {
     pub fn set_index_buffer(&mut self, buffer_slice: BufferSlice<'a>) {
         RenderInner::set_index_buffer(
             &mut self.id,
-            &buffer_slice.buffer.id,
+            buffer_slice.buffer.valid_id(),
             buffer_slice.offset,
             buffer_slice.size,
         )
@@ -2435,7 +3642,7 @@ impl<'a> RenderBundleEncoder<'a> {
         RenderInner::set_vertex_buffer(
             &mut self.id,
             slot,
-            &buffer_slice.buffer.id,
+            buffer_slice.buffer.valid_id(),
             buffer_slice.offset,
             buffer_slice.size,
         )
@@ -2472,7 +3679,8 @@ impl<'a> RenderBundleEncoder<'a> {
     /// }
     /// ```
     pub fn draw_indirect(&mut self, indirect_buffer: &'a Buffer, indirect_offset: BufferAddress) {
-        self.id.draw_indirect(&indirect_buffer.id, indirect_offset);
+        self.id
+            .draw_indirect(indirect_buffer.valid_id(), indirect_offset);
     }
 
     /// Draws indexed primitives using the active index buffer and the active vertex buffers,
@@ -2499,7 +3707,7 @@ impl<'a> RenderBundleEncoder<'a> {
         indirect_offset: BufferAddress,
     ) {
         self.id
-            .draw_indexed_indirect(&indirect_buffer.id, indirect_offset);
+            .draw_indexed_indirect(indirect_buffer.valid_id(), indirect_offset);
     }
 }
 
@@ -2541,7 +3749,7 @@ impl<'a> RenderBundleEncoder<'a> {
 impl Queue {
     /// Schedule a data write into `buffer` starting at `offset`.
     pub fn write_buffer(&self, buffer: &Buffer, offset: BufferAddress, data: &[u8]) {
-        Context::queue_write_buffer(&*self.context, &self.id, &buffer.id, offset, data)
+        Context::queue_write_buffer(&*self.context, &self.id, buffer.valid_id(), offset, data)
     }
 
     /// Schedule a data write into `texture`.
@@ -2570,8 +3778,13 @@ impl Queue {
 impl Drop for SwapChainTexture {
     fn drop(&mut self) {
         if !thread::panicking() {
-            Context::swap_chain_present(&*self.view.context, &self.view.id, &self.detail);
+            Context::swap_chain_present(
+                &*self.view.owner.context,
+                &self.view.owner.id,
+                &self.detail,
+            );
         }
+        self.view.owner.expire();
     }
 }
 
@@ -2588,9 +3801,17 @@ impl SwapChain {
             Context::swap_chain_get_current_texture_view(&*self.context, &self.id);
         let output = view_id.map(|id| SwapChainTexture {
             view: TextureView {
-                context: Arc::clone(&self.context),
-                id: id,
-                owned: false,
+                owner: Owner::unowned(&self.context, id),
+                descriptor: TextureViewDescriptor {
+                    label: None,
+                    format: Some(self.format),
+                    dimension: Some(TextureViewDimension::D2),
+                    aspect: TextureAspect::All,
+                    base_mip_level: 0,
+                    level_count: NonZeroU32::new(1),
+                    base_array_layer: 0,
+                    array_layer_count: NonZeroU32::new(1),
+                },
             },
             detail,
         });
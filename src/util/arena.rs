@@ -0,0 +1,311 @@
+use crate::{
+    Buffer, BufferAddress, BufferDescriptor, BufferSlice, BufferUsage, CommandEncoder, Device,
+    Queue, COPY_BUFFER_ALIGNMENT,
+};
+use std::ops::Range;
+
+/// Sorted list of the free ranges of a block, merged when adjacent.
+#[derive(Debug)]
+struct FreeList {
+    ranges: Vec<Range<BufferAddress>>,
+}
+
+impl FreeList {
+    fn new(size: BufferAddress) -> Self {
+        FreeList {
+            ranges: vec![0..size],
+        }
+    }
+
+    /// Takes the first free range that fits `size` bytes.
+    fn allocate(&mut self, size: BufferAddress) -> Option<Range<BufferAddress>> {
+        let index = self
+            .ranges
+            .iter()
+            .position(|range| range.end - range.start >= size)?;
+        let range = &mut self.ranges[index];
+        let start = range.start;
+        range.start += size;
+        if range.start == range.end {
+            self.ranges.remove(index);
+        }
+        Some(start..start + size)
+    }
+
+    fn free(&mut self, freed: Range<BufferAddress>) {
+        let index = self
+            .ranges
+            .iter()
+            .position(|range| range.start >= freed.end)
+            .unwrap_or_else(|| self.ranges.len());
+        debug_assert!(index == 0 || self.ranges[index - 1].end <= freed.start);
+
+        let merges_previous = index > 0 && self.ranges[index - 1].end == freed.start;
+        let merges_next = index < self.ranges.len() && self.ranges[index].start == freed.end;
+        match (merges_previous, merges_next) {
+            (true, true) => {
+                let next = self.ranges.remove(index);
+                self.ranges[index - 1].end = next.end;
+            }
+            (true, false) => self.ranges[index - 1].end = freed.end,
+            (false, true) => self.ranges[index].start = freed.start,
+            (false, false) => self.ranges.insert(index, freed),
+        }
+    }
+
+    fn free_size(&self) -> BufferAddress {
+        self.ranges
+            .iter()
+            .map(|range| range.end - range.start)
+            .sum()
+    }
+}
+
+struct Block {
+    buffer: Buffer,
+    size: BufferAddress,
+    free: FreeList,
+}
+
+/// Describes a [`BufferArena`].
+#[derive(Clone, Debug)]
+pub struct BufferArenaDescriptor<'a> {
+    /// Debug label of the backing buffers.
+    pub label: Option<&'a str>,
+    /// Size of each backing buffer. Allocations larger than this get a buffer of their own.
+    pub block_size: BufferAddress,
+    /// Usages of the backing buffers. `COPY_SRC` and `COPY_DST` are always added,
+    /// for uploads and defragmentation.
+    pub usage: BufferUsage,
+    /// Alignment of allocations, which must be a multiple of [`COPY_BUFFER_ALIGNMENT`].
+    pub alignment: BufferAddress,
+}
+
+/// Range of a backing buffer handed out by a [`BufferArena`].
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct BufferAllocation {
+    /// Index of the backing buffer in the arena.
+    pub buffer: usize,
+    /// Range of the allocation in the backing buffer.
+    pub range: Range<BufferAddress>,
+}
+
+/// Sub-allocator packing many small buffers, such as meshes, into a few large ones.
+///
+/// Allocations sharing a backing buffer can be drawn without rebinding it, by using
+/// their offsets as base vertex or first index.
+pub struct BufferArena {
+    label: Option<String>,
+    block_size: BufferAddress,
+    usage: BufferUsage,
+    alignment: BufferAddress,
+    blocks: Vec<Block>,
+}
+
+impl BufferArena {
+    /// Creates an empty arena. Backing buffers are created on demand.
+    pub fn new(desc: &BufferArenaDescriptor) -> Self {
+        assert!(desc.alignment > 0, "Alignment must not be zero");
+        assert_eq!(
+            desc.alignment % COPY_BUFFER_ALIGNMENT,
+            0,
+            "Alignment must be a multiple of COPY_BUFFER_ALIGNMENT"
+        );
+        BufferArena {
+            label: desc.label.map(String::from),
+            block_size: desc.block_size,
+            usage: desc.usage | BufferUsage::COPY_SRC | BufferUsage::COPY_DST,
+            alignment: desc.alignment,
+            blocks: Vec::new(),
+        }
+    }
+
+    fn create_buffer(&self, device: &Device, size: BufferAddress) -> Buffer {
+        device.create_buffer(&BufferDescriptor {
+            label: self.label.as_deref(),
+            size,
+            usage: self.usage,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// Allocates `size` bytes, creating a new backing buffer if none has enough room.
+    pub fn allocate(&mut self, device: &Device, size: BufferAddress) -> BufferAllocation {
+        let size =
+            ((size + self.alignment - 1) / self.alignment * self.alignment).max(self.alignment);
+        for (index, block) in self.blocks.iter_mut().enumerate() {
+            if let Some(range) = block.free.allocate(size) {
+                return BufferAllocation {
+                    buffer: index,
+                    range,
+                };
+            }
+        }
+
+        let block_size = self.block_size.max(size);
+        let mut block = Block {
+            buffer: self.create_buffer(device, block_size),
+            size: block_size,
+            free: FreeList::new(block_size),
+        };
+        let range = block.free.allocate(size).unwrap();
+        self.blocks.push(block);
+        BufferAllocation {
+            buffer: self.blocks.len() - 1,
+            range,
+        }
+    }
+
+    /// Returns an allocation to the arena.
+    pub fn free(&mut self, allocation: BufferAllocation) {
+        self.blocks[allocation.buffer].free.free(allocation.range);
+    }
+
+    /// Returns the backing buffer of an allocation.
+    pub fn buffer(&self, allocation: &BufferAllocation) -> &Buffer {
+        &self.blocks[allocation.buffer].buffer
+    }
+
+    /// Returns the slice of the backing buffer covered by an allocation.
+    pub fn slice(&self, allocation: &BufferAllocation) -> BufferSlice {
+        self.buffer(allocation).slice(allocation.range.clone())
+    }
+
+    /// Schedules writing data to the start of an allocation.
+    ///
+    /// See [`Queue::write_buffer`] for details.
+    pub fn write(&self, queue: &Queue, allocation: &BufferAllocation, data: &[u8]) {
+        assert!(
+            data.len() as BufferAddress <= allocation.range.end - allocation.range.start,
+            "Data is larger than the allocation"
+        );
+        queue.write_buffer(self.buffer(allocation), allocation.range.start, data);
+    }
+
+    /// Returns the total size of the backing buffers, and the size of their free ranges.
+    pub fn usage_stats(&self) -> (BufferAddress, BufferAddress) {
+        self.blocks.iter().fold((0, 0), |(total, free), block| {
+            (total + block.size, free + block.free.free_size())
+        })
+    }
+
+    /// Packs the allocations of fragmented backing buffers together, so that their
+    /// free space is contiguous.
+    ///
+    /// Every live allocation must be passed, and they are updated with their new ranges.
+    /// The data is moved by copies recorded into `encoder`, which must be submitted before
+    /// the allocations are used again. Backing buffers are replaced by new ones, so slices
+    /// and bind groups of the moved allocations must be recreated.
+    ///
+    /// Backing buffers left without any allocation are released, which changes the buffer
+    /// index of the allocations in the following ones.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the allocations passed don't cover all the used space of a backing buffer,
+    /// before any data is moved.
+    pub fn defragment<'a>(
+        &mut self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        allocations: impl IntoIterator<Item = &'a mut BufferAllocation>,
+    ) {
+        let mut per_block: Vec<Vec<&mut BufferAllocation>> =
+            self.blocks.iter().map(|_| Vec::new()).collect();
+        for allocation in allocations {
+            let index = allocation.buffer;
+            per_block[index].push(allocation);
+        }
+
+        // Check every block before moving anything, including the ones to release.
+        for (block, allocations) in self.blocks.iter().zip(per_block.iter()) {
+            let used: BufferAddress = allocations
+                .iter()
+                .map(|allocation| allocation.range.end - allocation.range.start)
+                .sum();
+            assert_eq!(
+                used + block.free.free_size(),
+                block.size,
+                "All live allocations must be passed to defragment"
+            );
+        }
+
+        for (index, allocations) in per_block.iter_mut().enumerate() {
+            // Blocks without allocations are released below.
+            if allocations.is_empty() {
+                continue;
+            }
+            // A single free range at the end means the block is already packed.
+            if self.blocks[index].free.ranges.len() <= 1
+                && self.blocks[index]
+                    .free
+                    .ranges
+                    .first()
+                    .map_or(true, |range| range.end == self.blocks[index].size)
+            {
+                continue;
+            }
+
+            allocations.sort_by_key(|allocation| allocation.range.start);
+            let block_size = self.blocks[index].size;
+
+            let buffer = self.create_buffer(device, block_size);
+            let mut offset = 0;
+            for allocation in allocations.iter_mut() {
+                let size = allocation.range.end - allocation.range.start;
+                encoder.copy_buffer_to_buffer(
+                    &self.blocks[index].buffer,
+                    allocation.range.start,
+                    &buffer,
+                    offset,
+                    size,
+                );
+                allocation.range = offset..offset + size;
+                offset += size;
+            }
+
+            let block = &mut self.blocks[index];
+            block.buffer = buffer;
+            block.free = FreeList { ranges: Vec::new() };
+            if offset < block_size {
+                block.free.ranges.push(offset..block_size);
+            }
+        }
+
+        let mut kept = 0;
+        for allocations in per_block
+            .iter_mut()
+            .filter(|allocations| !allocations.is_empty())
+        {
+            for allocation in allocations.iter_mut() {
+                allocation.buffer = kept;
+            }
+            kept += 1;
+        }
+        let mut blocks = per_block.iter();
+        self.blocks.retain(|_| {
+            blocks
+                .next()
+                .map_or(false, |allocations| !allocations.is_empty())
+        });
+    }
+}
+
+#[test]
+fn test_free_list() {
+    let mut list = FreeList::new(100);
+    let a = list.allocate(10).unwrap();
+    let b = list.allocate(20).unwrap();
+    let c = list.allocate(30).unwrap();
+    assert_eq!((a.clone(), b.clone(), c.clone()), (0..10, 10..30, 30..60));
+    assert_eq!(list.allocate(50), None);
+
+    list.free(a);
+    list.free(c);
+    assert_eq!(list.ranges, vec![0..10, 30..100]);
+    assert_eq!(list.allocate(15), Some(30..45));
+    list.free(30..45);
+    list.free(b);
+    assert_eq!(list.ranges, vec![0..100]);
+    assert_eq!(list.free_size(), 100);
+}
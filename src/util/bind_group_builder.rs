@@ -0,0 +1,615 @@
+use crate::{
+    BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutEntry,
+    BindingResource, BindingType, BufferAddress, BufferSize, BufferSlice, BufferUsage, Device,
+    Sampler, TextureComponentType, TextureFormat, TextureView, TextureViewDimension,
+};
+use std::{error::Error, fmt};
+
+/// Error occurred when building a bind group with a [`BindGroupBuilder`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BindGroupBuilderError {
+    /// The layout has no entry for this binding.
+    UnknownBinding(u32),
+    /// A resource was given more than once for this binding.
+    DuplicateBinding(u32),
+    /// No resource was given for a binding of the layout.
+    MissingBinding(u32),
+    /// The kind of resource doesn't match the type of the binding in the layout.
+    ResourceMismatch {
+        /// Index of the binding.
+        binding: u32,
+        /// Type of the binding in the layout.
+        expected: BindingType,
+        /// Kind of the resource that was given.
+        found: &'static str,
+    },
+    /// The number of resources doesn't match the count of the binding in the layout.
+    CountMismatch {
+        /// Index of the binding.
+        binding: u32,
+        /// Number of resources expected by the layout, if the binding is an array.
+        expected: Option<u32>,
+        /// Number of resources given, if an array was given.
+        found: Option<u32>,
+    },
+    /// The buffer wasn't created with the usage required by the binding.
+    MissingBufferUsage {
+        /// Index of the binding.
+        binding: u32,
+        /// Usage required by the binding.
+        required: BufferUsage,
+    },
+    /// The buffer slice is smaller than the minimum size of the binding.
+    BufferTooSmall {
+        /// Index of the binding.
+        binding: u32,
+        /// Size of the buffer slice.
+        size: BufferAddress,
+        /// Minimum size of the binding in the layout.
+        min_binding_size: BufferSize,
+    },
+    /// The sampler is a comparison sampler and the binding isn't, or the other way around.
+    SamplerComparisonMismatch {
+        /// Index of the binding.
+        binding: u32,
+        /// Whether the binding expects a comparison sampler.
+        comparison: bool,
+    },
+    /// The dimension of the texture view doesn't match the binding.
+    TextureViewDimensionMismatch {
+        /// Index of the binding.
+        binding: u32,
+        /// Dimension of the binding in the layout.
+        expected: TextureViewDimension,
+        /// Dimension of the texture view.
+        found: TextureViewDimension,
+    },
+    /// The format of the texture view isn't sampled as the component type of the binding.
+    TextureComponentTypeMismatch {
+        /// Index of the binding.
+        binding: u32,
+        /// Component type of the binding in the layout.
+        expected: TextureComponentType,
+        /// Component type of the format of the texture view.
+        found: TextureComponentType,
+    },
+    /// The format of the texture view doesn't match the storage texture binding.
+    StorageTextureFormatMismatch {
+        /// Index of the binding.
+        binding: u32,
+        /// Format of the binding in the layout.
+        expected: TextureFormat,
+        /// Format of the texture view.
+        found: TextureFormat,
+    },
+}
+
+impl fmt::Display for BindGroupBuilderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownBinding(binding) => {
+                write!(f, "Binding {} is not part of the layout", binding)
+            }
+            Self::DuplicateBinding(binding) => {
+                write!(f, "Binding {} was given more than once", binding)
+            }
+            Self::MissingBinding(binding) => {
+                write!(f, "No resource was given for binding {}", binding)
+            }
+            Self::ResourceMismatch {
+                binding,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Binding {} was given a {}, but the layout expects {:?}",
+                binding, found, expected
+            ),
+            Self::CountMismatch {
+                binding,
+                expected,
+                found,
+            } => {
+                write!(f, "Binding {} expects ", binding)?;
+                match expected {
+                    Some(count) => write!(f, "an array of {} resources", count)?,
+                    None => write!(f, "a single resource")?,
+                }
+                match found {
+                    Some(count) => write!(f, ", but was given an array of {}", count),
+                    None => write!(f, ", but was given a single resource"),
+                }
+            }
+            Self::MissingBufferUsage { binding, required } => write!(
+                f,
+                "The buffer given for binding {} is missing the usage {:?}",
+                binding, required
+            ),
+            Self::BufferTooSmall {
+                binding,
+                size,
+                min_binding_size,
+            } => write!(
+                f,
+                "The buffer slice given for binding {} is {} bytes long, \
+                 but the layout requires at least {} bytes",
+                binding, size, min_binding_size
+            ),
+            Self::SamplerComparisonMismatch {
+                binding,
+                comparison: true,
+            } => write!(f, "Binding {} expects a comparison sampler", binding),
+            Self::SamplerComparisonMismatch {
+                binding,
+                comparison: false,
+            } => write!(
+                f,
+                "Binding {} expects a sampler without a compare function",
+                binding
+            ),
+            Self::TextureViewDimensionMismatch {
+                binding,
+                expected,
+                found,
+            } => write!(
+                f,
+                "The texture view given for binding {} has the dimension {:?}, \
+                 but the layout expects {:?}",
+                binding, found, expected
+            ),
+            Self::TextureComponentTypeMismatch {
+                binding,
+                expected,
+                found,
+            } => write!(
+                f,
+                "The texture view given for binding {} has {:?} components, \
+                 but the layout expects {:?}",
+                binding, found, expected
+            ),
+            Self::StorageTextureFormatMismatch {
+                binding,
+                expected,
+                found,
+            } => write!(
+                f,
+                "The texture view given for binding {} has the format {:?}, \
+                 but the layout expects {:?}",
+                binding, found, expected
+            ),
+        }
+    }
+}
+
+impl Error for BindGroupBuilderError {}
+
+/// Builds a [`BindGroup`], checking its resources against the [`BindGroupLayout`].
+///
+/// The checks cover the kind, count and buffer usage of each resource, the minimum size of
+/// buffers, whether samplers compare, and the dimension and format of texture views.
+/// Whether a sampled texture is multisampled is not checked, as texture views don't know
+/// the sample count of their texture.
+///
+/// Layouts obtained with `get_bind_group_layout` on a pipeline don't know their entries,
+/// so nothing is checked for them unless the entries are given with
+/// [`layout_entries`](Self::layout_entries).
+#[derive(Clone, Debug)]
+pub struct BindGroupBuilder<'a> {
+    layout: &'a BindGroupLayout,
+    layout_entries: Option<&'a [BindGroupLayoutEntry]>,
+    label: Option<&'a str>,
+    entries: Vec<BindGroupEntry<'a>>,
+}
+
+impl<'a> BindGroupBuilder<'a> {
+    /// Creates a builder for a bind group with the given layout.
+    pub fn new(layout: &'a BindGroupLayout) -> Self {
+        Self {
+            layout,
+            layout_entries: layout.entries.as_deref(),
+            label: None,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Sets the debug label of the bind group.
+    pub fn label(&mut self, label: &'a str) -> &mut Self {
+        self.label = Some(label);
+        self
+    }
+
+    /// Sets the entries to check the resources against, in place of those of the layout.
+    ///
+    /// This is needed to check the resources bound to a layout obtained from a pipeline.
+    pub fn layout_entries(&mut self, entries: &'a [BindGroupLayoutEntry]) -> &mut Self {
+        self.layout_entries = Some(entries);
+        self
+    }
+
+    /// Binds a slice of a buffer.
+    pub fn buffer(&mut self, binding: u32, slice: BufferSlice<'a>) -> &mut Self {
+        self.resource(
+            binding,
+            BindingResource::Buffer {
+                buffer: slice.buffer,
+                offset: slice.offset,
+                size: slice.size,
+            },
+        )
+    }
+
+    /// Binds a texture view.
+    pub fn texture(&mut self, binding: u32, view: &'a TextureView) -> &mut Self {
+        self.resource(binding, BindingResource::TextureView(view))
+    }
+
+    /// Binds an array of texture views.
+    pub fn texture_array(&mut self, binding: u32, views: &'a [TextureView]) -> &mut Self {
+        self.resource(binding, BindingResource::TextureViewArray(views))
+    }
+
+    /// Binds a sampler.
+    pub fn sampler(&mut self, binding: u32, sampler: &'a Sampler) -> &mut Self {
+        self.resource(binding, BindingResource::Sampler(sampler))
+    }
+
+    /// Binds any resource.
+    pub fn resource(&mut self, binding: u32, resource: BindingResource<'a>) -> &mut Self {
+        self.entries.push(BindGroupEntry { binding, resource });
+        self
+    }
+
+    /// Checks the resources against the layout, then creates the bind group.
+    pub fn build(&self, device: &Device) -> Result<BindGroup, BindGroupBuilderError> {
+        self.validate()?;
+        Ok(device.create_bind_group(&BindGroupDescriptor {
+            label: self.label,
+            layout: self.layout,
+            entries: &self.entries,
+        }))
+    }
+
+    fn validate(&self) -> Result<(), BindGroupBuilderError> {
+        let layout_entries = match self.layout_entries {
+            Some(entries) => entries,
+            None => return Ok(()),
+        };
+
+        for (index, entry) in self.entries.iter().enumerate() {
+            let binding = entry.binding;
+            if self.entries[..index]
+                .iter()
+                .any(|other| other.binding == binding)
+            {
+                return Err(BindGroupBuilderError::DuplicateBinding(binding));
+            }
+            let layout_entry = layout_entries
+                .iter()
+                .find(|layout_entry| layout_entry.binding == binding)
+                .ok_or(BindGroupBuilderError::UnknownBinding(binding))?;
+
+            let count = match entry.resource {
+                BindingResource::TextureViewArray(views) => Some(views.len() as u32),
+                _ => None,
+            };
+            let expected_count = layout_entry.count.map(|count| count.get());
+            if count != expected_count {
+                return Err(BindGroupBuilderError::CountMismatch {
+                    binding,
+                    expected: expected_count,
+                    found: count,
+                });
+            }
+
+            match entry.resource {
+                BindingResource::Buffer {
+                    buffer,
+                    offset,
+                    size,
+                } => {
+                    // Without a size, the binding extends to the end of the buffer.
+                    let size = size
+                        .map_or_else(|| buffer.size().saturating_sub(offset), |size| size.get());
+                    check_buffer(layout_entry, buffer.usage(), size)?
+                }
+                BindingResource::Sampler(sampler) => check_sampler(layout_entry, sampler)?,
+                BindingResource::TextureView(view) => {
+                    check_texture_view(layout_entry, view.format(), view.dimension())?
+                }
+                BindingResource::TextureViewArray(views) => {
+                    for view in views {
+                        check_texture_view(layout_entry, view.format(), view.dimension())?;
+                    }
+                }
+            }
+        }
+
+        for layout_entry in layout_entries.iter() {
+            if !self
+                .entries
+                .iter()
+                .any(|entry| entry.binding == layout_entry.binding)
+            {
+                return Err(BindGroupBuilderError::MissingBinding(layout_entry.binding));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn mismatch(layout_entry: &BindGroupLayoutEntry, found: &'static str) -> BindGroupBuilderError {
+    BindGroupBuilderError::ResourceMismatch {
+        binding: layout_entry.binding,
+        expected: layout_entry.ty.clone(),
+        found,
+    }
+}
+
+fn check_buffer(
+    layout_entry: &BindGroupLayoutEntry,
+    usage: BufferUsage,
+    size: BufferAddress,
+) -> Result<(), BindGroupBuilderError> {
+    let binding = layout_entry.binding;
+    let (required, min_binding_size) = match layout_entry.ty {
+        BindingType::UniformBuffer {
+            min_binding_size, ..
+        } => (BufferUsage::UNIFORM, min_binding_size),
+        BindingType::StorageBuffer {
+            min_binding_size, ..
+        } => (BufferUsage::STORAGE, min_binding_size),
+        _ => return Err(mismatch(layout_entry, "buffer")),
+    };
+    if !usage.contains(required) {
+        return Err(BindGroupBuilderError::MissingBufferUsage { binding, required });
+    }
+    if let Some(min_binding_size) = min_binding_size {
+        if size < min_binding_size.get() {
+            return Err(BindGroupBuilderError::BufferTooSmall {
+                binding,
+                size,
+                min_binding_size,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn check_sampler(
+    layout_entry: &BindGroupLayoutEntry,
+    sampler: &Sampler,
+) -> Result<(), BindGroupBuilderError> {
+    check_sampler_comparison(layout_entry, sampler.descriptor().compare.is_some())
+}
+
+fn check_sampler_comparison(
+    layout_entry: &BindGroupLayoutEntry,
+    compare: bool,
+) -> Result<(), BindGroupBuilderError> {
+    match layout_entry.ty {
+        BindingType::Sampler { comparison } if comparison != compare => {
+            Err(BindGroupBuilderError::SamplerComparisonMismatch {
+                binding: layout_entry.binding,
+                comparison,
+            })
+        }
+        BindingType::Sampler { .. } => Ok(()),
+        _ => Err(mismatch(layout_entry, "sampler")),
+    }
+}
+
+fn check_texture_view(
+    layout_entry: &BindGroupLayoutEntry,
+    format: TextureFormat,
+    dimension: TextureViewDimension,
+) -> Result<(), BindGroupBuilderError> {
+    let binding = layout_entry.binding;
+    let expected_dimension = match layout_entry.ty {
+        BindingType::SampledTexture {
+            dimension,
+            component_type,
+            ..
+        } => {
+            let found = TextureComponentType::from(format);
+            if found != component_type {
+                return Err(BindGroupBuilderError::TextureComponentTypeMismatch {
+                    binding,
+                    expected: component_type,
+                    found,
+                });
+            }
+            dimension
+        }
+        BindingType::StorageTexture {
+            dimension,
+            format: expected,
+            ..
+        } => {
+            if format != expected {
+                return Err(BindGroupBuilderError::StorageTextureFormatMismatch {
+                    binding,
+                    expected,
+                    found: format,
+                });
+            }
+            dimension
+        }
+        _ => return Err(mismatch(layout_entry, "texture view")),
+    };
+    if dimension != expected_dimension {
+        return Err(BindGroupBuilderError::TextureViewDimensionMismatch {
+            binding,
+            expected: expected_dimension,
+            found: dimension,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+fn layout_entry(binding: u32, ty: BindingType) -> BindGroupLayoutEntry {
+    BindGroupLayoutEntry {
+        binding,
+        visibility: crate::ShaderStage::FRAGMENT,
+        ty,
+        count: None,
+    }
+}
+
+#[test]
+fn test_buffer_checks() {
+    let uniform = layout_entry(
+        0,
+        BindingType::UniformBuffer {
+            dynamic: false,
+            min_binding_size: BufferSize::new(64),
+        },
+    );
+    let usage = BufferUsage::UNIFORM | BufferUsage::COPY_DST;
+    assert_eq!(check_buffer(&uniform, usage, 64), Ok(()));
+    assert_eq!(
+        check_buffer(&uniform, BufferUsage::STORAGE, 64),
+        Err(BindGroupBuilderError::MissingBufferUsage {
+            binding: 0,
+            required: BufferUsage::UNIFORM,
+        })
+    );
+    assert_eq!(
+        check_buffer(&uniform, usage, 16),
+        Err(BindGroupBuilderError::BufferTooSmall {
+            binding: 0,
+            size: 16,
+            min_binding_size: BufferSize::new(64).unwrap(),
+        })
+    );
+
+    let sampler = layout_entry(1, BindingType::Sampler { comparison: false });
+    assert_eq!(
+        check_buffer(&sampler, usage, 64),
+        Err(mismatch(&sampler, "buffer"))
+    );
+}
+
+#[test]
+fn test_sampler_checks() {
+    let filtering = layout_entry(0, BindingType::Sampler { comparison: false });
+    let comparison = layout_entry(1, BindingType::Sampler { comparison: true });
+    assert_eq!(check_sampler_comparison(&filtering, false), Ok(()));
+    assert_eq!(check_sampler_comparison(&comparison, true), Ok(()));
+    assert_eq!(
+        check_sampler_comparison(&filtering, true),
+        Err(BindGroupBuilderError::SamplerComparisonMismatch {
+            binding: 0,
+            comparison: false,
+        })
+    );
+    assert_eq!(
+        check_sampler_comparison(&comparison, false),
+        Err(BindGroupBuilderError::SamplerComparisonMismatch {
+            binding: 1,
+            comparison: true,
+        })
+    );
+
+    let texture = layout_entry(
+        2,
+        BindingType::SampledTexture {
+            dimension: TextureViewDimension::D2,
+            component_type: TextureComponentType::Float,
+            multisampled: false,
+        },
+    );
+    assert_eq!(
+        check_sampler_comparison(&texture, false),
+        Err(mismatch(&texture, "sampler"))
+    );
+}
+
+#[test]
+fn test_texture_view_checks() {
+    let sampled = layout_entry(
+        0,
+        BindingType::SampledTexture {
+            dimension: TextureViewDimension::D2,
+            component_type: TextureComponentType::Float,
+            multisampled: false,
+        },
+    );
+    assert_eq!(
+        check_texture_view(
+            &sampled,
+            TextureFormat::Rgba8Unorm,
+            TextureViewDimension::D2
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        check_texture_view(
+            &sampled,
+            TextureFormat::Depth32Float,
+            TextureViewDimension::D2
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        check_texture_view(&sampled, TextureFormat::Rgba8Uint, TextureViewDimension::D2),
+        Err(BindGroupBuilderError::TextureComponentTypeMismatch {
+            binding: 0,
+            expected: TextureComponentType::Float,
+            found: TextureComponentType::Uint,
+        })
+    );
+    assert_eq!(
+        check_texture_view(
+            &sampled,
+            TextureFormat::Rgba8Unorm,
+            TextureViewDimension::Cube
+        ),
+        Err(BindGroupBuilderError::TextureViewDimensionMismatch {
+            binding: 0,
+            expected: TextureViewDimension::D2,
+            found: TextureViewDimension::Cube,
+        })
+    );
+
+    let storage = layout_entry(
+        1,
+        BindingType::StorageTexture {
+            dimension: TextureViewDimension::D2Array,
+            format: TextureFormat::R32Float,
+            readonly: true,
+        },
+    );
+    assert_eq!(
+        check_texture_view(
+            &storage,
+            TextureFormat::R32Float,
+            TextureViewDimension::D2Array
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        check_texture_view(
+            &storage,
+            TextureFormat::Rgba32Float,
+            TextureViewDimension::D2Array
+        ),
+        Err(BindGroupBuilderError::StorageTextureFormatMismatch {
+            binding: 1,
+            expected: TextureFormat::R32Float,
+            found: TextureFormat::Rgba32Float,
+        })
+    );
+
+    let uniform = layout_entry(
+        2,
+        BindingType::UniformBuffer {
+            dynamic: false,
+            min_binding_size: None,
+        },
+    );
+    assert_eq!(
+        check_texture_view(&uniform, TextureFormat::R32Float, TextureViewDimension::D2),
+        Err(mismatch(&uniform, "texture view"))
+    );
+}
@@ -0,0 +1,353 @@
+use super::{format_info, make_spirv, BlendPreset, RenderPipelineBuilder, StagingBelt};
+use crate::{
+    AddressMode, BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout,
+    BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingResource, BindingType, Buffer,
+    BufferAddress, BufferDescriptor, BufferSize, BufferUsage, CommandEncoder, Device, FilterMode,
+    LoadOp, Operations, PipelineLayout, PipelineLayoutDescriptor,
+    RenderPassColorAttachmentDescriptor, RenderPassDescriptor, RenderPipeline, Sampler,
+    SamplerDescriptor, ShaderModule, ShaderStage, Texture, TextureAspect, TextureComponentType,
+    TextureFormat, TextureView, TextureViewDescriptor, TextureViewDimension, WeakTexture,
+};
+use std::{collections::HashMap, future::Future, num::NonZeroU32};
+
+/// Size of the uniform holding the source rectangle, in texture coordinates.
+const RECT_SIZE: BufferAddress = 4 * 4;
+
+fn f32_bytes(values: &[f32]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(values.len() * 4);
+    for value in values {
+        bytes.extend_from_slice(&value.to_ne_bytes());
+    }
+    bytes
+}
+
+/// Rectangle of texels, starting from the top-left corner.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BlitRect {
+    /// Horizontal position of the left edge.
+    pub x: u32,
+    /// Vertical position of the top edge.
+    pub y: u32,
+    /// Width of the rectangle.
+    pub width: u32,
+    /// Height of the rectangle.
+    pub height: u32,
+}
+
+/// Texture read by a blit.
+#[derive(Clone, Debug)]
+pub struct BlitSource<'a> {
+    /// Texture to read, which must have the `SAMPLED` usage, a float format and a single sample.
+    pub texture: &'a Texture,
+    /// Mip level to read.
+    pub mip_level: u32,
+    /// Array layer to read.
+    pub array_layer: u32,
+    /// Rectangle of the mip level to read. If `None`, the whole mip level is read.
+    pub rect: Option<BlitRect>,
+}
+
+/// Texture written by a blit.
+#[derive(Clone, Debug)]
+pub struct BlitDestination<'a> {
+    /// View of a single mip level and array layer of a texture with the `OUTPUT_ATTACHMENT` usage,
+    /// such as the frame of a swap chain.
+    pub view: &'a TextureView,
+    /// Rectangle to write. If `None`, the whole view is written.
+    pub rect: Option<BlitRect>,
+}
+
+/// Copies between textures of different formats and sizes, by drawing with a sampled texture.
+///
+/// Unlike [`CommandEncoder::copy_texture_to_texture`], blits convert between formats, such as
+/// from `Rgba16Float` to `Bgra8UnormSrgb` for presenting, and scale the source rectangle
+/// to the destination rectangle with the given filtering.
+///
+/// A pipeline is created for each pair of source and destination formats the first time
+/// it is blitted, and a bind group for each source mip level and array layer, then they are
+/// reused by later blits.
+///
+/// The source rectangle of each blit is uploaded with a [`StagingBelt`], so like with
+/// a belt, [`finish`](Self::finish) must be called before submitting the encoders used
+/// for blitting, and [`recall`](Self::recall) after.
+pub struct Blitter {
+    vertex_shader: ShaderModule,
+    fragment_shader: ShaderModule,
+    bind_group_layout: BindGroupLayout,
+    pipeline_layout: PipelineLayout,
+    rect: Buffer,
+    belt: StagingBelt,
+    nearest_sampler: Sampler,
+    linear_sampler: Sampler,
+    pipelines: HashMap<(TextureFormat, TextureFormat), RenderPipeline>,
+    /// Keyed by the address of the owner of the source texture, which stays allocated
+    /// as long as the weak handle is kept, then by mip level, array layer and filter.
+    bind_groups: HashMap<(usize, u32, u32, FilterMode), (WeakTexture, BindGroup)>,
+}
+
+impl Blitter {
+    /// Creates a blitter, without any pipeline.
+    pub fn new(device: &Device) -> Self {
+        let vertex_shader =
+            device.create_shader_module(make_spirv(include_bytes!("blit.vert.spv")));
+        let fragment_shader =
+            device.create_shader_module(make_spirv(include_bytes!("blit.frag.spv")));
+        let bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("blit"),
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStage::VERTEX,
+                    ty: BindingType::UniformBuffer {
+                        dynamic: false,
+                        min_binding_size: BufferSize::new(RECT_SIZE),
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStage::FRAGMENT,
+                    ty: BindingType::SampledTexture {
+                        dimension: TextureViewDimension::D2,
+                        component_type: TextureComponentType::Float,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: ShaderStage::FRAGMENT,
+                    ty: BindingType::Sampler { comparison: false },
+                    count: None,
+                },
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("blit"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let rect = device.create_buffer(&BufferDescriptor {
+            label: Some("blit rect"),
+            size: RECT_SIZE,
+            usage: BufferUsage::UNIFORM | BufferUsage::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let create_sampler = |filter| {
+            device.create_sampler(&SamplerDescriptor {
+                label: Some("blit"),
+                address_mode_u: AddressMode::ClampToEdge,
+                address_mode_v: AddressMode::ClampToEdge,
+                address_mode_w: AddressMode::ClampToEdge,
+                mag_filter: filter,
+                min_filter: filter,
+                mipmap_filter: FilterMode::Nearest,
+                ..Default::default()
+            })
+        };
+
+        Blitter {
+            vertex_shader,
+            fragment_shader,
+            bind_group_layout,
+            pipeline_layout,
+            rect,
+            belt: StagingBelt::new(256 * RECT_SIZE),
+            nearest_sampler: create_sampler(FilterMode::Nearest),
+            linear_sampler: create_sampler(FilterMode::Linear),
+            pipelines: HashMap::new(),
+            bind_groups: HashMap::new(),
+        }
+    }
+
+    /// Records a blit from `source` to `destination` into `encoder`, scaling the source
+    /// rectangle to the destination rectangle with `filter`.
+    ///
+    /// # Panics
+    ///
+    /// - Source texture is multisampled. Use [`resolve`](Self::resolve) instead.
+    /// - Source texture doesn't have a float format.
+    /// - `filter` is [`FilterMode::Linear`] and the source format isn't filterable.
+    /// - Destination format isn't color renderable.
+    pub fn blit(
+        &mut self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        source: &BlitSource,
+        destination: &BlitDestination,
+        filter: FilterMode,
+    ) {
+        let desc = source.texture.descriptor();
+        assert_eq!(
+            desc.sample_count, 1,
+            "Multisampled textures must be resolved with `Blitter::resolve`"
+        );
+        assert_eq!(
+            TextureComponentType::from(desc.format),
+            TextureComponentType::Float,
+            "Blit source must have a float format, not {:?}",
+            desc.format
+        );
+        assert!(
+            filter == FilterMode::Nearest || format_info(desc.format).filterable,
+            "Blit source format {:?} can't be filtered linearly",
+            desc.format
+        );
+
+        let source_format = desc.format;
+        let destination_format = destination.view.format();
+        let Blitter {
+            ref vertex_shader,
+            ref fragment_shader,
+            ref bind_group_layout,
+            ref pipeline_layout,
+            ref rect,
+            ref mut belt,
+            ref nearest_sampler,
+            ref linear_sampler,
+            ref mut pipelines,
+            ref mut bind_groups,
+        } = *self;
+        let pipeline = pipelines
+            .entry((source_format, destination_format))
+            .or_insert_with(|| {
+                RenderPipelineBuilder::new()
+                    .label("blit")
+                    .layout(pipeline_layout)
+                    .vertex_shader(vertex_shader, "main")
+                    .fragment_shader(fragment_shader, "main")
+                    .color_target(destination_format, BlendPreset::Replace)
+                    .build(device)
+                    .unwrap_or_else(|error| {
+                        panic!("Cannot blit to {:?}: {}", destination_format, error)
+                    })
+            });
+
+        let key = (
+            &*source.texture.owner as *const _ as usize,
+            source.mip_level,
+            source.array_layer,
+            filter,
+        );
+        if !bind_groups.contains_key(&key) {
+            // Forget the sources that were dropped since the last new one.
+            bind_groups.retain(|_, (texture, _)| texture.owner.strong_count() > 0);
+            let view = source.texture.create_view(&TextureViewDescriptor {
+                label: Some("blit"),
+                format: None,
+                dimension: Some(TextureViewDimension::D2),
+                aspect: TextureAspect::All,
+                base_mip_level: source.mip_level,
+                level_count: NonZeroU32::new(1),
+                base_array_layer: source.array_layer,
+                array_layer_count: NonZeroU32::new(1),
+            });
+            let bind_group = device.create_bind_group(&BindGroupDescriptor {
+                label: Some("blit"),
+                layout: bind_group_layout,
+                entries: &[
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: rect.as_entire_binding(),
+                    },
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: BindingResource::TextureView(&view),
+                    },
+                    BindGroupEntry {
+                        binding: 2,
+                        resource: BindingResource::Sampler(match filter {
+                            FilterMode::Nearest => nearest_sampler,
+                            FilterMode::Linear => linear_sampler,
+                        }),
+                    },
+                ],
+            });
+            bind_groups.insert(key, (source.texture.downgrade(), bind_group));
+        }
+        let (_, bind_group) = &bind_groups[&key];
+
+        let width = (desc.size.width >> source.mip_level).max(1);
+        let height = (desc.size.height >> source.mip_level).max(1);
+        let source_rect = source.rect.unwrap_or(BlitRect {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        });
+        belt.write_buffer(
+            encoder,
+            rect,
+            0,
+            BufferSize::new(RECT_SIZE).unwrap(),
+            device,
+        )
+        .copy_from_slice(&f32_bytes(&[
+            source_rect.x as f32 / width as f32,
+            source_rect.y as f32 / height as f32,
+            source_rect.width as f32 / width as f32,
+            source_rect.height as f32 / height as f32,
+        ]));
+
+        let mut pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("blit"),
+            color_attachments: &[RenderPassColorAttachmentDescriptor {
+                attachment: destination.view,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Load,
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+        });
+        if let Some(rect) = destination.rect {
+            pass.set_viewport(
+                rect.x as f32,
+                rect.y as f32,
+                rect.width as f32,
+                rect.height as f32,
+                0.0,
+                1.0,
+            );
+        }
+        pass.set_pipeline(pipeline);
+        pass.set_bind_group(0, bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+
+    /// Prepares the uploads of the source rectangles for submission.
+    ///
+    /// See [`StagingBelt::finish`].
+    pub fn finish(&mut self) {
+        self.belt.finish();
+    }
+
+    /// Recalls the staging buffers of the source rectangles, once the encoders used for
+    /// blitting are submitted.
+    ///
+    /// See [`StagingBelt::recall`].
+    pub fn recall(&mut self) -> impl Future<Output = ()> + Send {
+        self.belt.recall()
+    }
+
+    /// Records the resolve of the multisampled `source` into `destination` into `encoder`.
+    ///
+    /// Both views must have the same format and size, and `destination` must not be
+    /// multisampled. The resolve is done by an empty render pass, so that it can happen
+    /// separately from the pass that rendered to `source`.
+    pub fn resolve(encoder: &mut CommandEncoder, source: &TextureView, destination: &TextureView) {
+        encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("resolve"),
+            color_attachments: &[RenderPassColorAttachmentDescriptor {
+                attachment: source,
+                resolve_target: Some(destination),
+                ops: Operations {
+                    load: LoadOp::Load,
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+        });
+    }
+}
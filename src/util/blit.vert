@@ -0,0 +1,16 @@
+#version 450
+
+layout(location = 0) out vec2 v_TexCoord;
+
+layout(set = 0, binding = 0) uniform Locals {
+    // Origin and size of the source rectangle, in texture coordinates.
+    vec4 u_Rect;
+};
+
+void main() {
+    // A single triangle covers the viewport, with the corners of the source rectangle
+    // at the corners of the viewport.
+    vec2 corner = vec2(float((gl_VertexIndex << 1) & 2), float(gl_VertexIndex & 2));
+    v_TexCoord = u_Rect.xy + corner * u_Rect.zw;
+    gl_Position = vec4(corner.x * 2.0 - 1.0, 1.0 - corner.y * 2.0, 0.0, 1.0);
+}
@@ -0,0 +1,385 @@
+use crate::ShaderModuleSource;
+use std::{borrow::Cow, collections::HashMap, error::Error, fmt};
+
+/// Location of a line in one of the files registered with a [`ShaderComposer`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SourceLocation {
+    /// Name of the file, as registered with [`ShaderComposer::add_file`].
+    pub file: String,
+    /// Line in the file, starting at 1.
+    pub line: u32,
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)
+    }
+}
+
+/// Kind of error that occurred when composing a shader.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ComposeErrorKind {
+    /// The named file was not registered with the composer.
+    FileNotFound(String),
+    /// The directive is not one of the supported ones.
+    UnknownDirective(String),
+    /// The directive is missing its argument, or the argument is malformed.
+    MalformedDirective(String),
+    /// `#else` appeared outside of a conditional block, or twice in the same block.
+    UnexpectedElse,
+    /// `#endif` appeared outside of a conditional block.
+    UnexpectedEndif,
+    /// The file ended inside of a conditional block.
+    UnterminatedConditional,
+}
+
+/// Error occurred when composing a shader.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ComposeError {
+    /// Where the error occurred. `None` if the entry file itself is missing.
+    pub location: Option<SourceLocation>,
+    /// What went wrong.
+    pub kind: ComposeErrorKind,
+}
+
+impl fmt::Display for ComposeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(ref location) = self.location {
+            write!(f, "{}: ", location)?;
+        }
+        match self.kind {
+            ComposeErrorKind::FileNotFound(ref name) => write!(f, "file \"{}\" not found", name),
+            ComposeErrorKind::UnknownDirective(ref name) => {
+                write!(f, "unknown directive \"#{}\"", name)
+            }
+            ComposeErrorKind::MalformedDirective(ref name) => {
+                write!(f, "malformed directive \"#{}\"", name)
+            }
+            ComposeErrorKind::UnexpectedElse => write!(f, "unexpected \"#else\""),
+            ComposeErrorKind::UnexpectedEndif => write!(f, "unexpected \"#endif\""),
+            ComposeErrorKind::UnterminatedConditional => {
+                write!(f, "conditional block is missing \"#endif\"")
+            }
+        }
+    }
+}
+
+impl Error for ComposeError {}
+
+/// WGSL source produced by a [`ShaderComposer`], along with its source map.
+#[derive(Clone, Debug)]
+pub struct ComposedShader {
+    /// The composed, self-contained WGSL code.
+    pub code: String,
+    files: Vec<String>,
+    /// For every line of `code`, the index into `files` and the original line.
+    lines: Vec<(usize, u32)>,
+}
+
+impl ComposedShader {
+    /// Get the source to pass to [`Device::create_shader_module`](crate::Device::create_shader_module).
+    pub fn source(&self) -> ShaderModuleSource<'_> {
+        ShaderModuleSource::Wgsl(Cow::Borrowed(&self.code))
+    }
+
+    /// Map a line of the composed code, starting at 1, back to the file and line it came from.
+    pub fn map_line(&self, line: u32) -> Option<SourceLocation> {
+        let index = (line as usize).checked_sub(1)?;
+        self.lines.get(index).map(|&(file, line)| SourceLocation {
+            file: self.files[file].clone(),
+            line,
+        })
+    }
+}
+
+struct Conditional {
+    parent_active: bool,
+    condition: bool,
+    seen_else: bool,
+}
+
+impl Conditional {
+    fn active(&self) -> bool {
+        self.parent_active && (self.condition != self.seen_else)
+    }
+}
+
+struct Composition<'a> {
+    defines: HashMap<String, String>,
+    included: Vec<&'a str>,
+    code: String,
+    lines: Vec<(usize, u32)>,
+}
+
+/// Composes WGSL shaders out of files sharing common code.
+///
+/// The following directives are supported, each on its own line:
+/// - `#include "name"` inserts a file registered with `add_file`.
+///   Every file is included at most once per composition.
+/// - `#define NAME [value]` and `#undef NAME`. Defines with a value are
+///   substituted wherever `NAME` appears as an identifier in the following code.
+/// - `#ifdef NAME`, `#ifndef NAME`, `#else` and `#endif`.
+///
+/// The composed shader keeps track of where each of its lines came from,
+/// so errors reported against it can be mapped back with [`ComposedShader::map_line`].
+#[derive(Clone, Debug, Default)]
+pub struct ShaderComposer {
+    files: HashMap<String, String>,
+    defines: HashMap<String, String>,
+}
+
+impl ShaderComposer {
+    /// Create a new composer without any files or defines.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a file that can be composed or included under `name`.
+    pub fn add_file(&mut self, name: impl Into<String>, code: impl Into<String>) -> &mut Self {
+        self.files.insert(name.into(), code.into());
+        self
+    }
+
+    /// Define `name` for every composition, as if by `#define name value`.
+    pub fn define(&mut self, name: impl Into<String>, value: impl Into<String>) -> &mut Self {
+        self.defines.insert(name.into(), value.into());
+        self
+    }
+
+    /// Remove a define previously set with `define`.
+    pub fn undefine(&mut self, name: &str) -> &mut Self {
+        self.defines.remove(name);
+        self
+    }
+
+    /// Compose the registered file `entry` into a single WGSL source.
+    pub fn compose(&self, entry: &str) -> Result<ComposedShader, ComposeError> {
+        let mut composition = Composition {
+            defines: self.defines.clone(),
+            included: Vec::new(),
+            code: String::new(),
+            lines: Vec::new(),
+        };
+        self.compose_file(&mut composition, entry, None)?;
+        Ok(ComposedShader {
+            code: composition.code,
+            files: composition
+                .included
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+            lines: composition.lines,
+        })
+    }
+
+    fn compose_file<'a>(
+        &'a self,
+        composition: &mut Composition<'a>,
+        name: &str,
+        from: Option<SourceLocation>,
+    ) -> Result<(), ComposeError> {
+        let (name, code) = match self.files.get_key_value(name) {
+            Some((name, code)) => (name.as_str(), code.as_str()),
+            None => {
+                return Err(ComposeError {
+                    location: from,
+                    kind: ComposeErrorKind::FileNotFound(name.to_string()),
+                })
+            }
+        };
+        if composition.included.contains(&name) {
+            return Ok(());
+        }
+        let file_index = composition.included.len();
+        composition.included.push(name);
+
+        let mut conditionals: Vec<Conditional> = Vec::new();
+        let mut line_number = 0;
+        for line in code.lines() {
+            line_number += 1;
+            let location = || SourceLocation {
+                file: name.to_string(),
+                line: line_number,
+            };
+            let error = |kind| ComposeError {
+                location: Some(location()),
+                kind,
+            };
+            let active = conditionals.last().map_or(true, Conditional::active);
+
+            let trimmed = line.trim_start();
+            if !trimmed.starts_with('#') {
+                if active {
+                    substitute(&composition.defines, line, &mut composition.code);
+                    composition.code.push('\n');
+                    composition.lines.push((file_index, line_number));
+                }
+                continue;
+            }
+
+            let mut parts = trimmed[1..].trim().splitn(2, char::is_whitespace);
+            let directive = parts.next().unwrap_or("");
+            let argument = parts.next().map_or("", str::trim);
+            let malformed = || error(ComposeErrorKind::MalformedDirective(directive.to_string()));
+            match directive {
+                "ifdef" | "ifndef" => {
+                    if !is_identifier(argument) {
+                        return Err(malformed());
+                    }
+                    let defined = composition.defines.contains_key(argument);
+                    conditionals.push(Conditional {
+                        parent_active: active,
+                        condition: defined == (directive == "ifdef"),
+                        seen_else: false,
+                    });
+                }
+                "else" => match conditionals.last_mut() {
+                    Some(conditional) if !conditional.seen_else => conditional.seen_else = true,
+                    _ => return Err(error(ComposeErrorKind::UnexpectedElse)),
+                },
+                "endif" => {
+                    if conditionals.pop().is_none() {
+                        return Err(error(ComposeErrorKind::UnexpectedEndif));
+                    }
+                }
+                _ if !active => {}
+                "include" => {
+                    let included = argument
+                        .strip_prefix('"')
+                        .and_then(|arg| arg.strip_suffix('"'))
+                        .filter(|arg| !arg.is_empty())
+                        .ok_or_else(malformed)?;
+                    self.compose_file(composition, included, Some(location()))?;
+                }
+                "define" => {
+                    let mut parts = argument.splitn(2, char::is_whitespace);
+                    let define = parts.next().unwrap_or("");
+                    if !is_identifier(define) {
+                        return Err(malformed());
+                    }
+                    let value = parts.next().map_or("", str::trim);
+                    composition
+                        .defines
+                        .insert(define.to_string(), value.to_string());
+                }
+                "undef" => {
+                    if !is_identifier(argument) {
+                        return Err(malformed());
+                    }
+                    composition.defines.remove(argument);
+                }
+                _ => {
+                    return Err(error(ComposeErrorKind::UnknownDirective(
+                        directive.to_string(),
+                    )))
+                }
+            }
+        }
+
+        if !conditionals.is_empty() {
+            return Err(ComposeError {
+                location: Some(SourceLocation {
+                    file: name.to_string(),
+                    line: line_number,
+                }),
+                kind: ComposeErrorKind::UnterminatedConditional,
+            });
+        }
+        Ok(())
+    }
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn is_identifier(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && s.chars().all(is_identifier_char)
+}
+
+/// Append `line` to `out`, replacing identifiers that have a defined value.
+fn substitute(defines: &HashMap<String, String>, line: &str, out: &mut String) {
+    let mut rest = line;
+    while let Some(start) = rest.find(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        // Identifiers never start in the middle of a number literal like `1e5`.
+        let (before, ident_start) = rest.split_at(start);
+        let in_literal = before.ends_with(|c: char| c.is_ascii_digit() || c == '.');
+        let end = ident_start
+            .find(|c: char| !is_identifier_char(c))
+            .unwrap_or_else(|| ident_start.len());
+        let (ident, after) = ident_start.split_at(end);
+        out.push_str(before);
+        match defines.get(ident) {
+            Some(value) if !in_literal && !value.is_empty() => out.push_str(value),
+            _ => out.push_str(ident),
+        }
+        rest = after;
+    }
+    out.push_str(rest);
+}
+
+#[test]
+fn test_include_and_source_map() {
+    let mut composer = ShaderComposer::new();
+    composer
+        .add_file("common.wgsl", "const A: f32 = 1.0;\nconst B: f32 = 2.0;")
+        .add_file(
+            "main.wgsl",
+            "#include \"common.wgsl\"\n#include \"common.wgsl\"\nfn main() {}",
+        );
+    let shader = composer.compose("main.wgsl").unwrap();
+    assert_eq!(
+        shader.code,
+        "const A: f32 = 1.0;\nconst B: f32 = 2.0;\nfn main() {}\n"
+    );
+    let location = |file: &str, line| {
+        Some(SourceLocation {
+            file: file.to_string(),
+            line,
+        })
+    };
+    assert_eq!(shader.map_line(2), location("common.wgsl", 2));
+    assert_eq!(shader.map_line(3), location("main.wgsl", 3));
+    assert_eq!(shader.map_line(4), None);
+}
+
+#[test]
+fn test_defines_and_conditionals() {
+    let mut composer = ShaderComposer::new();
+    composer.define("SHADOWS", "").add_file(
+        "main.wgsl",
+        "#define COUNT 4\n#ifdef SHADOWS\na\n#ifndef COUNT\nb\n#else\nc COUNT\n#endif\n#else\nd\n#endif",
+    );
+    assert_eq!(composer.compose("main.wgsl").unwrap().code, "a\nc 4\n");
+    composer.undefine("SHADOWS");
+    assert_eq!(composer.compose("main.wgsl").unwrap().code, "d\n");
+}
+
+#[test]
+fn test_compose_errors() {
+    let mut composer = ShaderComposer::new();
+    composer
+        .add_file("missing.wgsl", "\n#include \"other.wgsl\"")
+        .add_file("open.wgsl", "#ifdef A")
+        .add_file("unknown.wgsl", "#pragma once");
+    assert_eq!(
+        composer.compose("missing.wgsl").unwrap_err(),
+        ComposeError {
+            location: Some(SourceLocation {
+                file: "missing.wgsl".to_string(),
+                line: 2,
+            }),
+            kind: ComposeErrorKind::FileNotFound("other.wgsl".to_string()),
+        }
+    );
+    assert_eq!(
+        composer.compose("open.wgsl").unwrap_err().kind,
+        ComposeErrorKind::UnterminatedConditional
+    );
+    assert_eq!(
+        composer.compose("unknown.wgsl").unwrap_err().kind,
+        ComposeErrorKind::UnknownDirective("pragma".to_string())
+    );
+    assert_eq!(composer.compose("none.wgsl").unwrap_err().location, None);
+}
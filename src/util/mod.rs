@@ -1,6 +1,20 @@
 //! Utility structures and functions.
 
+mod arena;
 mod belt;
+mod bind_group_builder;
+mod blit;
+mod composer;
+mod encoder;
+mod pipeline_builder;
+#[cfg(all(feature = "pipeline-cache", not(target_arch = "wasm32")))]
+mod pipeline_cache;
+mod registry;
+mod shader_type;
+mod texture_pool;
+mod uniform_ring;
+#[cfg(all(feature = "shader-watcher", not(target_arch = "wasm32")))]
+mod watcher;
 
 use std::{
     borrow::Cow,
@@ -8,8 +22,33 @@ use std::{
     ptr::copy_nonoverlapping,
 };
 
+pub use crate::format::{format_info, FormatInfo};
+pub use arena::{BufferAllocation, BufferArena, BufferArenaDescriptor};
 pub use belt::StagingBelt;
+pub use bind_group_builder::{BindGroupBuilder, BindGroupBuilderError};
+pub use blit::{BlitDestination, BlitRect, BlitSource, Blitter};
+pub use composer::{
+    ComposeError, ComposeErrorKind, ComposedShader, ShaderComposer, SourceLocation,
+};
+pub use encoder::RenderEncoder;
+pub use pipeline_builder::{BlendPreset, RenderPipelineBuilder, RenderPipelineBuilderError};
+#[cfg(all(feature = "pipeline-cache", not(target_arch = "wasm32")))]
+pub use pipeline_cache::PipelineCache;
+pub use registry::{
+    ComputePipelineKey, PipelineRegistry, PipelineRegistryError, ProgrammableStageKey,
+    RenderPipelineKey, VertexBufferKey,
+};
+pub use shader_type::{MemoryLayout, ShaderType};
 use std::sync::Arc;
+pub use texture_pool::{PooledTexture, TexturePool, TexturePoolStats};
+pub use uniform_ring::{UniformRing, UniformRingDescriptor};
+#[cfg(all(feature = "shader-watcher", not(target_arch = "wasm32")))]
+pub use watcher::{
+    ShaderWatcher, ShaderWatcherError, WatchedComputePipeline, WatchedComputePipelineDescriptor,
+    WatchedRenderPipeline, WatchedRenderPipelineDescriptor, WatchedShader, WatchedStageDescriptor,
+};
+#[cfg(feature = "derive")]
+pub use wgpu_macros::ShaderType;
 
 /// Treat the given byte slice as a SPIR-V module.
 ///
@@ -74,15 +113,23 @@ impl DeviceExt for crate::Device {
         let mut map_context = crate::MapContext::new(padded_size);
 
         map_context.initial_range = 0..padded_size;
+        let id = crate::Context::device_create_buffer(&*self.context, &self.id, &wgt_descriptor);
         let buffer = crate::Buffer {
-            context: Arc::clone(&self.context),
-            id: crate::Context::device_create_buffer(&*self.context, &self.id, &wgt_descriptor),
-            map_context: parking_lot::Mutex::new(map_context),
-            usage: descriptor.usage,
+            owner: crate::Owner::new(&self.context, id, |context, id| {
+                crate::Context::buffer_drop(context, id)
+            }),
+            map_context: Arc::new(parking_lot::Mutex::new(map_context)),
+            descriptor: crate::BufferDescriptor {
+                label: None,
+                ..wgt_descriptor.clone()
+            },
         };
 
-        let range =
-            crate::Context::buffer_get_mapped_range_mut(&*self.context, &buffer.id, 0..padded_size);
+        let range = crate::Context::buffer_get_mapped_range_mut(
+            &*self.context,
+            &buffer.owner.id,
+            0..padded_size,
+        );
         range[0..unpadded_size as usize].copy_from_slice(descriptor.contents);
         for i in unpadded_size..padded_size {
             range[i as usize] = 0;
@@ -0,0 +1,519 @@
+use super::format_info;
+use crate::{
+    BlendDescriptor, BlendFactor, BlendOperation, ColorStateDescriptor, ColorWrite,
+    CompareFunction, Context, CreatePipelineError, CullMode, DepthStencilStateDescriptor, Device,
+    FrontFace, IndexFormat, PipelineLayout, PrimitiveTopology, ProgrammableStageDescriptor,
+    RasterizationStateDescriptor, RenderPipeline, RenderPipelineDescriptor, ShaderModule,
+    ShaderStage, StencilStateDescriptor, StencilStateFaceDescriptor, TextureComponentType,
+    TextureFormat, VertexBufferDescriptor, VertexStateDescriptor,
+};
+use std::{error::Error, fmt};
+
+/// Common ways of blending the output of a fragment shader with a color target.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BlendPreset {
+    /// Overwrite the target with the output.
+    Replace,
+    /// Standard alpha blending, `src * src_alpha + dst * (1 - src_alpha)`.
+    Alpha,
+    /// Add the output, weighted by its alpha, to the target.
+    Additive,
+    /// Alpha blending for colors that are already multiplied by their alpha,
+    /// `src + dst * (1 - src_alpha)`.
+    PremultipliedAlpha,
+}
+
+impl BlendPreset {
+    /// Returns the blending of the color channels.
+    pub fn color_blend(self) -> BlendDescriptor {
+        match self {
+            Self::Replace => BlendDescriptor::REPLACE,
+            Self::Alpha => BlendDescriptor {
+                src_factor: BlendFactor::SrcAlpha,
+                dst_factor: BlendFactor::OneMinusSrcAlpha,
+                operation: BlendOperation::Add,
+            },
+            Self::Additive => BlendDescriptor {
+                src_factor: BlendFactor::SrcAlpha,
+                dst_factor: BlendFactor::One,
+                operation: BlendOperation::Add,
+            },
+            Self::PremultipliedAlpha => BlendDescriptor {
+                src_factor: BlendFactor::One,
+                dst_factor: BlendFactor::OneMinusSrcAlpha,
+                operation: BlendOperation::Add,
+            },
+        }
+    }
+
+    /// Returns the blending of the alpha channel.
+    pub fn alpha_blend(self) -> BlendDescriptor {
+        match self {
+            Self::Replace => BlendDescriptor::REPLACE,
+            Self::Alpha | Self::PremultipliedAlpha => BlendDescriptor {
+                src_factor: BlendFactor::One,
+                dst_factor: BlendFactor::OneMinusSrcAlpha,
+                operation: BlendOperation::Add,
+            },
+            Self::Additive => BlendDescriptor {
+                src_factor: BlendFactor::One,
+                dst_factor: BlendFactor::One,
+                operation: BlendOperation::Add,
+            },
+        }
+    }
+}
+
+/// Error occurred when building a render pipeline with a [`RenderPipelineBuilder`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RenderPipelineBuilderError {
+    /// No vertex shader was set.
+    MissingVertexStage,
+    /// The format of a color target can't be rendered to.
+    UnrenderableColorFormat {
+        /// Index of the color target.
+        index: usize,
+        /// Format of the color target.
+        format: TextureFormat,
+    },
+    /// Blending is enabled on a color target whose format doesn't support it.
+    UnblendableColorFormat {
+        /// Index of the color target.
+        index: usize,
+        /// Format of the color target.
+        format: TextureFormat,
+    },
+    /// The depth-stencil format isn't a depth format.
+    NotDepthFormat(TextureFormat),
+    /// The shader module of a stage failed to compile, see [`ShaderModule::compilation_info`].
+    InvalidShaderModule(ShaderStage),
+    /// The fragment shader writes to a location without a color target.
+    MissingColorTarget {
+        /// Location of the fragment shader output.
+        location: u32,
+    },
+    /// The type of a fragment shader output doesn't match the format of its color target.
+    ComponentTypeMismatch {
+        /// Location of the fragment shader output.
+        location: u32,
+        /// Format of the color target.
+        format: TextureFormat,
+        /// Type of the fragment shader output.
+        output: TextureComponentType,
+    },
+    /// The device failed to create the pipeline.
+    CreatePipeline(CreatePipelineError),
+}
+
+impl fmt::Display for RenderPipelineBuilderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingVertexStage => write!(f, "No vertex shader was set"),
+            Self::UnrenderableColorFormat { index, format } => write!(
+                f,
+                "Color target {} has format {:?}, which can't be rendered to",
+                index, format
+            ),
+            Self::UnblendableColorFormat { index, format } => write!(
+                f,
+                "Color target {} has format {:?}, which doesn't support blending",
+                index, format
+            ),
+            Self::NotDepthFormat(format) => {
+                write!(f, "Depth-stencil format {:?} isn't a depth format", format)
+            }
+            Self::InvalidShaderModule(stage) => {
+                write!(
+                    f,
+                    "Shader module of the {:?} stage failed to compile",
+                    stage
+                )
+            }
+            Self::MissingColorTarget { location } => write!(
+                f,
+                "Fragment shader writes to location {}, which has no color target",
+                location
+            ),
+            Self::ComponentTypeMismatch {
+                location,
+                format,
+                output,
+            } => write!(
+                f,
+                "Fragment shader output at location {} is of type {:?}, \
+                 but its color target has format {:?}",
+                location, output, format
+            ),
+            Self::CreatePipeline(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for RenderPipelineBuilderError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::CreatePipeline(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Builds a [`RenderPipeline`], starting from the defaults of WebGPU.
+///
+/// Unless changed, the pipeline draws a triangle list with counter-clockwise front faces
+/// and no culling, has no depth-stencil state, isn't multisampled, and uses 32-bit indices.
+///
+/// When possible, [`build`](Self::build) checks the color targets against the outputs of the
+/// fragment shader. Shader reflection is only available on native, for WGSL modules when
+/// the `wgsl-validation` feature is enabled, and for SPIR-V modules created with
+/// [`Device::create_specializable_shader_module`].
+#[derive(Clone, Debug)]
+pub struct RenderPipelineBuilder<'a> {
+    label: Option<&'a str>,
+    layout: Option<&'a PipelineLayout>,
+    vertex_stage: Option<ProgrammableStageDescriptor<'a>>,
+    fragment_stage: Option<ProgrammableStageDescriptor<'a>>,
+    rasterization_state: RasterizationStateDescriptor,
+    primitive_topology: PrimitiveTopology,
+    color_states: Vec<ColorStateDescriptor>,
+    depth_stencil_state: Option<DepthStencilStateDescriptor>,
+    index_format: IndexFormat,
+    vertex_buffers: Vec<VertexBufferDescriptor<'a>>,
+    sample_count: u32,
+    sample_mask: u32,
+    alpha_to_coverage_enabled: bool,
+}
+
+impl Default for RenderPipelineBuilder<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> RenderPipelineBuilder<'a> {
+    /// Creates a builder with the default state.
+    pub fn new() -> Self {
+        Self {
+            label: None,
+            layout: None,
+            vertex_stage: None,
+            fragment_stage: None,
+            rasterization_state: RasterizationStateDescriptor::default(),
+            primitive_topology: PrimitiveTopology::TriangleList,
+            color_states: Vec::new(),
+            depth_stencil_state: None,
+            index_format: IndexFormat::Uint32,
+            vertex_buffers: Vec::new(),
+            sample_count: 1,
+            sample_mask: !0,
+            alpha_to_coverage_enabled: false,
+        }
+    }
+
+    /// Sets the debug label of the pipeline.
+    pub fn label(&mut self, label: &'a str) -> &mut Self {
+        self.label = Some(label);
+        self
+    }
+
+    /// Sets the layout of bind groups for the pipeline.
+    pub fn layout(&mut self, layout: &'a PipelineLayout) -> &mut Self {
+        self.layout = Some(layout);
+        self
+    }
+
+    /// Sets the vertex shader.
+    pub fn vertex_shader(&mut self, module: &'a ShaderModule, entry_point: &'a str) -> &mut Self {
+        self.vertex_stage = Some(ProgrammableStageDescriptor {
+            module,
+            entry_point,
+            constants: &[],
+        });
+        self
+    }
+
+    /// Sets the fragment shader.
+    pub fn fragment_shader(&mut self, module: &'a ShaderModule, entry_point: &'a str) -> &mut Self {
+        self.fragment_stage = Some(ProgrammableStageDescriptor {
+            module,
+            entry_point,
+            constants: &[],
+        });
+        self
+    }
+
+    /// Sets the vertex stage, including pipeline-overridable constants.
+    pub fn vertex_stage(&mut self, stage: ProgrammableStageDescriptor<'a>) -> &mut Self {
+        self.vertex_stage = Some(stage);
+        self
+    }
+
+    /// Sets the fragment stage, including pipeline-overridable constants.
+    pub fn fragment_stage(&mut self, stage: ProgrammableStageDescriptor<'a>) -> &mut Self {
+        self.fragment_stage = Some(stage);
+        self
+    }
+
+    /// Sets the primitive topology used to interpret vertices.
+    pub fn primitive_topology(&mut self, topology: PrimitiveTopology) -> &mut Self {
+        self.primitive_topology = topology;
+        self
+    }
+
+    /// Sets which faces are culled.
+    pub fn cull_mode(&mut self, cull_mode: CullMode) -> &mut Self {
+        self.rasterization_state.cull_mode = cull_mode;
+        self
+    }
+
+    /// Sets the winding order of front faces.
+    pub fn front_face(&mut self, front_face: FrontFace) -> &mut Self {
+        self.rasterization_state.front_face = front_face;
+        self
+    }
+
+    /// Replaces the whole rasterization state.
+    pub fn rasterization_state(&mut self, state: RasterizationStateDescriptor) -> &mut Self {
+        self.rasterization_state = state;
+        self
+    }
+
+    /// Adds a color target with the given format and blending, writing to all channels.
+    pub fn color_target(&mut self, format: TextureFormat, blend: BlendPreset) -> &mut Self {
+        self.color_states.push(ColorStateDescriptor {
+            format,
+            color_blend: blend.color_blend(),
+            alpha_blend: blend.alpha_blend(),
+            write_mask: ColorWrite::ALL,
+        });
+        self
+    }
+
+    /// Adds a color target with a custom state.
+    pub fn color_state(&mut self, state: ColorStateDescriptor) -> &mut Self {
+        self.color_states.push(state);
+        self
+    }
+
+    /// Enables depth testing, without stencil testing.
+    pub fn depth(
+        &mut self,
+        format: TextureFormat,
+        compare: CompareFunction,
+        write_enabled: bool,
+    ) -> &mut Self {
+        self.depth_stencil_state = Some(DepthStencilStateDescriptor {
+            format,
+            depth_write_enabled: write_enabled,
+            depth_compare: compare,
+            stencil: StencilStateDescriptor {
+                front: StencilStateFaceDescriptor::IGNORE,
+                back: StencilStateFaceDescriptor::IGNORE,
+                read_mask: !0,
+                write_mask: !0,
+            },
+        });
+        self
+    }
+
+    /// Replaces the whole depth-stencil state.
+    pub fn depth_stencil_state(&mut self, state: DepthStencilStateDescriptor) -> &mut Self {
+        self.depth_stencil_state = Some(state);
+        self
+    }
+
+    /// Sets the number of samples per pixel.
+    pub fn multisample(&mut self, sample_count: u32) -> &mut Self {
+        self.sample_count = sample_count;
+        self
+    }
+
+    /// Sets the mask of samples modified by the pipeline.
+    pub fn sample_mask(&mut self, sample_mask: u32) -> &mut Self {
+        self.sample_mask = sample_mask;
+        self
+    }
+
+    /// Enables or disables alpha to coverage.
+    pub fn alpha_to_coverage(&mut self, enabled: bool) -> &mut Self {
+        self.alpha_to_coverage_enabled = enabled;
+        self
+    }
+
+    /// Sets the format of index buffers used with the pipeline.
+    pub fn index_format(&mut self, format: IndexFormat) -> &mut Self {
+        self.index_format = format;
+        self
+    }
+
+    /// Adds a vertex buffer.
+    pub fn vertex_buffer(&mut self, buffer: VertexBufferDescriptor<'a>) -> &mut Self {
+        self.vertex_buffers.push(buffer);
+        self
+    }
+
+    /// Checks the state of the builder, then creates the pipeline.
+    ///
+    /// Returns an error if a check fails, or if the device rejects the pipeline.
+    pub fn build(&self, device: &Device) -> Result<RenderPipeline, RenderPipelineBuilderError> {
+        let vertex_stage = self
+            .vertex_stage
+            .clone()
+            .ok_or(RenderPipelineBuilderError::MissingVertexStage)?;
+        if vertex_stage.module.owner.id.is_none() {
+            return Err(RenderPipelineBuilderError::InvalidShaderModule(
+                ShaderStage::VERTEX,
+            ));
+        }
+        self.validate()?;
+
+        device
+            .try_create_render_pipeline(&RenderPipelineDescriptor {
+                label: self.label,
+                layout: self.layout,
+                vertex_stage,
+                fragment_stage: self.fragment_stage.clone(),
+                rasterization_state: Some(self.rasterization_state.clone()),
+                primitive_topology: self.primitive_topology,
+                color_states: &self.color_states,
+                depth_stencil_state: self.depth_stencil_state.clone(),
+                vertex_state: VertexStateDescriptor {
+                    index_format: self.index_format,
+                    vertex_buffers: &self.vertex_buffers,
+                },
+                sample_count: self.sample_count,
+                sample_mask: self.sample_mask,
+                alpha_to_coverage_enabled: self.alpha_to_coverage_enabled,
+            })
+            .map_err(RenderPipelineBuilderError::CreatePipeline)
+    }
+
+    fn validate(&self) -> Result<(), RenderPipelineBuilderError> {
+        for (index, state) in self.color_states.iter().enumerate() {
+            let info = format_info(state.format);
+            if !info.renderable || !info.is_color() {
+                return Err(RenderPipelineBuilderError::UnrenderableColorFormat {
+                    index,
+                    format: state.format,
+                });
+            }
+            let blending = state.color_blend != BlendDescriptor::REPLACE
+                || state.alpha_blend != BlendDescriptor::REPLACE;
+            if blending && !info.blendable {
+                return Err(RenderPipelineBuilderError::UnblendableColorFormat {
+                    index,
+                    format: state.format,
+                });
+            }
+        }
+
+        if let Some(ref state) = self.depth_stencil_state {
+            if !format_info(state.format).has_depth {
+                return Err(RenderPipelineBuilderError::NotDepthFormat(state.format));
+            }
+        }
+
+        let stage = match self.fragment_stage {
+            Some(ref stage) => stage,
+            None => return Ok(()),
+        };
+        let module = match stage.module.owner.id {
+            Some(ref id) => id,
+            None => {
+                return Err(RenderPipelineBuilderError::InvalidShaderModule(
+                    ShaderStage::FRAGMENT,
+                ))
+            }
+        };
+        let outputs = match Context::shader_module_fragment_outputs(
+            &*stage.module.owner.context,
+            module,
+            stage.entry_point,
+        ) {
+            Some(outputs) => outputs,
+            None => return Ok(()),
+        };
+        for output in outputs {
+            let format = self
+                .color_states
+                .get(output.location as usize)
+                .map(|state| state.format)
+                .ok_or(RenderPipelineBuilderError::MissingColorTarget {
+                    location: output.location,
+                })?;
+            if format_info(format).component_type != output.component_type {
+                return Err(RenderPipelineBuilderError::ComponentTypeMismatch {
+                    location: output.location,
+                    format,
+                    output: output.component_type,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_color_target_checks() {
+    use RenderPipelineBuilderError::*;
+
+    let validate = |format, blend| {
+        RenderPipelineBuilder::new()
+            .color_target(TextureFormat::Rgba8UnormSrgb, BlendPreset::Alpha)
+            .color_target(format, blend)
+            .validate()
+    };
+    assert_eq!(
+        validate(TextureFormat::Rgba16Float, BlendPreset::Additive),
+        Ok(())
+    );
+    assert_eq!(
+        validate(TextureFormat::R32Uint, BlendPreset::Replace),
+        Ok(())
+    );
+    assert_eq!(
+        validate(TextureFormat::R32Uint, BlendPreset::Alpha),
+        Err(UnblendableColorFormat {
+            index: 1,
+            format: TextureFormat::R32Uint,
+        })
+    );
+    assert_eq!(
+        validate(TextureFormat::Rgba32Float, BlendPreset::PremultipliedAlpha),
+        Err(UnblendableColorFormat {
+            index: 1,
+            format: TextureFormat::Rgba32Float,
+        })
+    );
+    assert_eq!(
+        validate(TextureFormat::Depth32Float, BlendPreset::Replace),
+        Err(UnrenderableColorFormat {
+            index: 1,
+            format: TextureFormat::Depth32Float,
+        })
+    );
+    assert_eq!(
+        validate(TextureFormat::Bc1RgbaUnorm, BlendPreset::Replace),
+        Err(UnrenderableColorFormat {
+            index: 1,
+            format: TextureFormat::Bc1RgbaUnorm,
+        })
+    );
+}
+
+#[test]
+fn test_depth_format_checks() {
+    let validate = |format| {
+        RenderPipelineBuilder::new()
+            .depth(format, CompareFunction::Less, true)
+            .validate()
+    };
+    assert_eq!(validate(TextureFormat::Depth32Float), Ok(()));
+    assert_eq!(validate(TextureFormat::Depth24PlusStencil8), Ok(()));
+    assert_eq!(
+        validate(TextureFormat::R32Float),
+        Err(RenderPipelineBuilderError::NotDepthFormat(
+            TextureFormat::R32Float
+        ))
+    );
+}
@@ -0,0 +1,271 @@
+use super::registry::{
+    ComputePipelineKey, PipelineRegistry, PipelineRegistryError, RenderPipelineKey,
+};
+use crate::{
+    AdapterInfo, ComputePipeline, Device, RenderPipeline, ShaderModule, ShaderModuleSource,
+};
+use bincode::Options as _;
+use serde::{Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashSet},
+};
+
+/// Owned code of a shader module.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+enum ShaderCode {
+    SpirV(Vec<u32>),
+    Wgsl(String),
+}
+
+impl ShaderCode {
+    fn source(&self) -> ShaderModuleSource<'_> {
+        match self {
+            Self::SpirV(words) => ShaderModuleSource::SpirV(Cow::Borrowed(words)),
+            Self::Wgsl(code) => ShaderModuleSource::Wgsl(Cow::Borrowed(code)),
+        }
+    }
+}
+
+impl From<ShaderModuleSource<'_>> for ShaderCode {
+    fn from(source: ShaderModuleSource) -> Self {
+        match source {
+            ShaderModuleSource::SpirV(words) => Self::SpirV(words.into_owned()),
+            ShaderModuleSource::Wgsl(code) => Self::Wgsl(code.into_owned()),
+        }
+    }
+}
+
+/// Contents of a [`PipelineCache`] that are saved.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheData {
+    fingerprint: String,
+    shader_modules: BTreeMap<String, ShaderCode>,
+    render_pipelines: HashSet<RenderPipelineKey>,
+    compute_pipelines: HashSet<ComputePipelineKey>,
+}
+
+/// Records the pipelines of a [`PipelineRegistry`], so that they can be saved and
+/// created ahead of time by later runs of the application.
+///
+/// wgpu-core doesn't expose the pipeline caches of the drivers, so instead of backend
+/// data, the cache holds the code of the shader modules added through it, and the keys
+/// of the pipelines created through it. On the next start, [`PipelineCache::warm_up`]
+/// creates all of them, before they are needed for rendering.
+///
+/// Saved data is tagged with a fingerprint of the adapter and of this library,
+/// and is discarded when loaded by a different combination. wgpu-core doesn't report
+/// the version of the driver, so it isn't part of the fingerprint.
+#[derive(Debug)]
+pub struct PipelineCache {
+    data: CacheData,
+}
+
+impl PipelineCache {
+    const MAGIC: &'static [u8; 8] = b"WGPUPLCH";
+    const FORMAT_VERSION: u32 = 1;
+
+    /// Create an empty cache for pipelines created on `adapter_info`.
+    pub fn new(adapter_info: &AdapterInfo) -> Self {
+        PipelineCache {
+            data: CacheData {
+                fingerprint: Self::fingerprint(adapter_info),
+                ..CacheData::default()
+            },
+        }
+    }
+
+    /// Load a cache saved with [`PipelineCache::save`].
+    ///
+    /// If `bytes` are corrupted, or were saved with another adapter or version of
+    /// this library, they are discarded and the cache is empty.
+    pub fn load(adapter_info: &AdapterInfo, bytes: &[u8]) -> Self {
+        let fingerprint = Self::fingerprint(adapter_info);
+        match Self::parse(bytes) {
+            Some(data) if data.fingerprint == fingerprint => PipelineCache { data },
+            _ => Self::new(adapter_info),
+        }
+    }
+
+    fn fingerprint(adapter_info: &AdapterInfo) -> String {
+        format!(
+            "wgpu {}, {} ({:x}:{:x}), {:?}, {:?}",
+            env!("CARGO_PKG_VERSION"),
+            adapter_info.name,
+            adapter_info.vendor,
+            adapter_info.device,
+            adapter_info.device_type,
+            adapter_info.backend,
+        )
+    }
+
+    fn parse(bytes: &[u8]) -> Option<CacheData> {
+        if !bytes.starts_with(Self::MAGIC) {
+            return None;
+        }
+        let rest = &bytes[Self::MAGIC.len()..];
+        if rest.len() < 4 {
+            return None;
+        }
+        let (version, rest) = rest.split_at(4);
+        if version != &Self::FORMAT_VERSION.to_le_bytes()[..] {
+            return None;
+        }
+        bincode::DefaultOptions::new()
+            .with_limit(rest.len() as u64)
+            .deserialize(rest)
+            .ok()
+    }
+
+    /// Save the cache, to be loaded with [`PipelineCache::load`].
+    pub fn save(&self) -> Vec<u8> {
+        let mut bytes = Self::MAGIC.to_vec();
+        bytes.extend_from_slice(&Self::FORMAT_VERSION.to_le_bytes());
+        bytes.extend(
+            bincode::DefaultOptions::new()
+                .serialize(&self.data)
+                .expect("Pipeline cache data is always serializable"),
+        );
+        bytes
+    }
+
+    /// Returns `true` if the cache has no shader modules and no pipelines.
+    pub fn is_empty(&self) -> bool {
+        self.data.shader_modules.is_empty()
+            && self.data.render_pipelines.is_empty()
+            && self.data.compute_pipelines.is_empty()
+    }
+
+    /// Create a shader module from `source`, and register it in `registry` under `id`.
+    ///
+    /// The code is kept in the cache, replacing any previous code for `id`. The module
+    /// is created with [`Device::create_specializable_shader_module`], so that keys
+    /// can override its constants.
+    ///
+    /// Returns the module previously registered under `id`, see
+    /// [`PipelineRegistry::add_shader_module`].
+    pub fn add_shader_module(
+        &mut self,
+        registry: &mut PipelineRegistry,
+        device: &Device,
+        id: impl Into<String>,
+        source: ShaderModuleSource,
+    ) -> Option<ShaderModule> {
+        let id = id.into();
+        let code = ShaderCode::from(source);
+        let module = device.create_specializable_shader_module(code.source());
+        self.data.shader_modules.insert(id.clone(), code);
+        registry.add_shader_module(id, module)
+    }
+
+    /// Get the render pipeline described by `key` from `registry`, and record it in the cache.
+    ///
+    /// See [`PipelineRegistry::render_pipeline`].
+    pub fn render_pipeline<'a>(
+        &mut self,
+        registry: &'a mut PipelineRegistry,
+        device: &Device,
+        key: &RenderPipelineKey,
+    ) -> Result<&'a RenderPipeline, PipelineRegistryError> {
+        let pipeline = registry.render_pipeline(device, key)?;
+        if !self.data.render_pipelines.contains(key) {
+            self.data.render_pipelines.insert(key.clone());
+        }
+        Ok(pipeline)
+    }
+
+    /// Get the compute pipeline described by `key` from `registry`, and record it in the cache.
+    ///
+    /// See [`PipelineRegistry::compute_pipeline`].
+    pub fn compute_pipeline<'a>(
+        &mut self,
+        registry: &'a mut PipelineRegistry,
+        device: &Device,
+        key: &ComputePipelineKey,
+    ) -> Result<&'a ComputePipeline, PipelineRegistryError> {
+        let pipeline = registry.compute_pipeline(device, key)?;
+        if !self.data.compute_pipelines.contains(key) {
+            self.data.compute_pipelines.insert(key.clone());
+        }
+        Ok(pipeline)
+    }
+
+    /// Create all the recorded pipelines in `registry`.
+    ///
+    /// Shader modules whose code is in the cache are created and registered first, unless
+    /// `registry` already has a module with the same ID. Pipeline layouts aren't recorded,
+    /// so they have to be registered before calling this.
+    ///
+    /// Pipelines that can't be created anymore are removed from the cache, and the errors
+    /// are returned.
+    pub fn warm_up(
+        &mut self,
+        registry: &mut PipelineRegistry,
+        device: &Device,
+    ) -> Vec<PipelineRegistryError> {
+        for (id, code) in self.data.shader_modules.iter() {
+            if registry.shader_module(id).is_none() {
+                let module = device.create_specializable_shader_module(code.source());
+                registry.add_shader_module(id.clone(), module);
+            }
+        }
+
+        let mut errors = Vec::new();
+        self.data
+            .render_pipelines
+            .retain(|key| match registry.render_pipeline(device, key) {
+                Ok(_) => true,
+                Err(err) => {
+                    errors.push(err);
+                    false
+                }
+            });
+        self.data
+            .compute_pipelines
+            .retain(|key| match registry.compute_pipeline(device, key) {
+                Ok(_) => true,
+                Err(err) => {
+                    errors.push(err);
+                    false
+                }
+            });
+        errors
+    }
+}
+
+#[test]
+fn test_load_discards_stale_data() {
+    use super::ProgrammableStageKey;
+    use crate::{Backend, DeviceType};
+
+    let adapter_info = |name: &str| AdapterInfo {
+        name: name.to_string(),
+        vendor: 0x10de,
+        device: 0x1b80,
+        device_type: DeviceType::DiscreteGpu,
+        backend: Backend::Vulkan,
+    };
+    let mut cache = PipelineCache::new(&adapter_info("GPU"));
+    cache.data.shader_modules.insert(
+        "shader".to_string(),
+        ShaderCode::Wgsl("fn main() -> void {}".to_string()),
+    );
+    cache.data.compute_pipelines.insert(ComputePipelineKey {
+        layout: None,
+        compute_stage: ProgrammableStageKey {
+            module: "shader".to_string(),
+            entry_point: "main".to_string(),
+            constants: vec![("0".to_string(), 1.5)],
+        },
+    });
+    let bytes = cache.save();
+
+    let loaded = PipelineCache::load(&adapter_info("GPU"), &bytes);
+    assert_eq!(loaded.data.shader_modules, cache.data.shader_modules);
+    assert_eq!(loaded.data.compute_pipelines, cache.data.compute_pipelines);
+
+    assert!(PipelineCache::load(&adapter_info("Other GPU"), &bytes).is_empty());
+    assert!(PipelineCache::load(&adapter_info("GPU"), &bytes[..bytes.len() - 1]).is_empty());
+    assert!(PipelineCache::load(&adapter_info("GPU"), &bytes[1..]).is_empty());
+    assert!(PipelineCache::load(&adapter_info("GPU"), &[]).is_empty());
+}
@@ -0,0 +1,490 @@
+use crate::{
+    BufferAddress, ColorStateDescriptor, ComputePipeline, ComputePipelineDescriptor,
+    CreatePipelineError, CullMode, DepthStencilStateDescriptor, Device, FrontFace, IndexFormat,
+    InputStepMode, PipelineLayout, PrimitiveTopology, ProgrammableStageDescriptor,
+    RasterizationStateDescriptor, RenderPipeline, RenderPipelineDescriptor, ShaderModule,
+    VertexAttributeDescriptor, VertexBufferDescriptor, VertexStateDescriptor,
+};
+#[cfg(any(feature = "replay", feature = "pipeline-cache"))]
+use serde::Deserialize;
+#[cfg(any(feature = "trace", feature = "pipeline-cache"))]
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+/// Owned description of a programmable stage, referring to its shader module by ID.
+#[derive(Clone, Debug)]
+#[cfg_attr(any(feature = "trace", feature = "pipeline-cache"), derive(Serialize))]
+#[cfg_attr(
+    any(feature = "replay", feature = "pipeline-cache"),
+    derive(Deserialize)
+)]
+pub struct ProgrammableStageKey {
+    /// ID of the shader module, as registered with [`PipelineRegistry::add_shader_module`].
+    pub module: String,
+    /// The name of the entry point in the compiled shader.
+    pub entry_point: String,
+    /// Values of pipeline-overridable constants, see [`ProgrammableStageDescriptor::constants`].
+    pub constants: Vec<(String, f64)>,
+}
+
+// Constants are compared bitwise, so that keys can implement `Eq` and `Hash`.
+impl PartialEq for ProgrammableStageKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.module == other.module
+            && self.entry_point == other.entry_point
+            && self.constants.len() == other.constants.len()
+            && self
+                .constants
+                .iter()
+                .zip(other.constants.iter())
+                .all(|(a, b)| a.0 == b.0 && a.1.to_bits() == b.1.to_bits())
+    }
+}
+
+impl Eq for ProgrammableStageKey {}
+
+impl Hash for ProgrammableStageKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.module.hash(state);
+        self.entry_point.hash(state);
+        self.constants.len().hash(state);
+        for &(ref key, value) in self.constants.iter() {
+            key.hash(state);
+            value.to_bits().hash(state);
+        }
+    }
+}
+
+/// Owned version of [`VertexBufferDescriptor`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(any(feature = "trace", feature = "pipeline-cache"), derive(Serialize))]
+#[cfg_attr(
+    any(feature = "replay", feature = "pipeline-cache"),
+    derive(Deserialize)
+)]
+pub struct VertexBufferKey {
+    /// The stride, in bytes, between elements of this buffer.
+    pub stride: BufferAddress,
+    /// How often this vertex buffer is "stepped" forward.
+    pub step_mode: InputStepMode,
+    /// The list of attributes which comprise a single vertex.
+    pub attributes: Vec<VertexAttributeDescriptor>,
+}
+
+impl VertexBufferKey {
+    /// Borrow the key as a [`VertexBufferDescriptor`].
+    pub fn as_descriptor(&self) -> VertexBufferDescriptor {
+        VertexBufferDescriptor {
+            stride: self.stride,
+            step_mode: self.step_mode,
+            attributes: &self.attributes,
+        }
+    }
+}
+
+/// Owned, hashable description of a render pipeline.
+///
+/// Mirrors [`RenderPipelineDescriptor`], except that shader modules and pipeline
+/// layouts are referred to by the IDs they were registered under in a [`PipelineRegistry`],
+/// and that there is no label.
+#[derive(Clone, Debug)]
+#[cfg_attr(any(feature = "trace", feature = "pipeline-cache"), derive(Serialize))]
+#[cfg_attr(
+    any(feature = "replay", feature = "pipeline-cache"),
+    derive(Deserialize)
+)]
+pub struct RenderPipelineKey {
+    /// ID of the pipeline layout, or `None` to derive it from the shaders.
+    pub layout: Option<String>,
+    /// The vertex stage and its entry point.
+    pub vertex_stage: ProgrammableStageKey,
+    /// The fragment stage and its entry point, if any.
+    pub fragment_stage: Option<ProgrammableStageKey>,
+    /// The rasterization process for this pipeline.
+    pub rasterization_state: Option<RasterizationStateDescriptor>,
+    /// The primitive topology used to interpret vertices.
+    pub primitive_topology: PrimitiveTopology,
+    /// The effect of draw calls on the color aspect of the output target.
+    pub color_states: Vec<ColorStateDescriptor>,
+    /// The effect of draw calls on the depth and stencil aspects of the output target, if any.
+    pub depth_stencil_state: Option<DepthStencilStateDescriptor>,
+    /// The format of any index buffers used with this pipeline.
+    pub index_format: IndexFormat,
+    /// The format of any vertex buffers used with this pipeline.
+    pub vertex_buffers: Vec<VertexBufferKey>,
+    /// The number of samples calculated per pixel (for MSAA).
+    pub sample_count: u32,
+    /// Bitmask that restricts the samples of a pixel modified by this pipeline.
+    pub sample_mask: u32,
+    /// Enables alpha to coverage, see [`RenderPipelineDescriptor::alpha_to_coverage_enabled`].
+    pub alpha_to_coverage_enabled: bool,
+}
+
+impl RenderPipelineKey {
+    /// All the fields, with the floating point ones replaced by their bits.
+    #[allow(clippy::type_complexity)]
+    fn as_tuple(
+        &self,
+    ) -> (
+        &Option<String>,
+        &ProgrammableStageKey,
+        &Option<ProgrammableStageKey>,
+        Option<(FrontFace, CullMode, bool, i32, u32, u32)>,
+        PrimitiveTopology,
+        &[ColorStateDescriptor],
+        &Option<DepthStencilStateDescriptor>,
+        IndexFormat,
+        &[VertexBufferKey],
+        u32,
+        u32,
+        bool,
+    ) {
+        (
+            &self.layout,
+            &self.vertex_stage,
+            &self.fragment_stage,
+            self.rasterization_state.as_ref().map(|state| {
+                (
+                    state.front_face,
+                    state.cull_mode,
+                    state.clamp_depth,
+                    state.depth_bias,
+                    state.depth_bias_slope_scale.to_bits(),
+                    state.depth_bias_clamp.to_bits(),
+                )
+            }),
+            self.primitive_topology,
+            &self.color_states,
+            &self.depth_stencil_state,
+            self.index_format,
+            &self.vertex_buffers,
+            self.sample_count,
+            self.sample_mask,
+            self.alpha_to_coverage_enabled,
+        )
+    }
+
+    fn uses_shader_module(&self, id: &str) -> bool {
+        self.vertex_stage.module == id
+            || self
+                .fragment_stage
+                .as_ref()
+                .map_or(false, |stage| stage.module == id)
+    }
+}
+
+impl PartialEq for RenderPipelineKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_tuple() == other.as_tuple()
+    }
+}
+
+impl Eq for RenderPipelineKey {}
+
+impl Hash for RenderPipelineKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_tuple().hash(state);
+    }
+}
+
+/// Owned, hashable description of a compute pipeline.
+///
+/// See [`RenderPipelineKey`] for how it relates to [`ComputePipelineDescriptor`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(any(feature = "trace", feature = "pipeline-cache"), derive(Serialize))]
+#[cfg_attr(
+    any(feature = "replay", feature = "pipeline-cache"),
+    derive(Deserialize)
+)]
+pub struct ComputePipelineKey {
+    /// ID of the pipeline layout, or `None` to derive it from the shader.
+    pub layout: Option<String>,
+    /// The compute stage and its entry point.
+    pub compute_stage: ProgrammableStageKey,
+}
+
+/// Error occurred when creating a pipeline from a key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PipelineRegistryError {
+    /// No shader module is registered with this ID.
+    UnknownShaderModule(String),
+    /// No pipeline layout is registered with this ID.
+    UnknownPipelineLayout(String),
+    /// The shader module registered with this ID failed to compile,
+    /// see [`ShaderModule::compilation_info`].
+    InvalidShaderModule(String),
+    /// The device failed to create the pipeline.
+    CreatePipeline(CreatePipelineError),
+}
+
+impl fmt::Display for PipelineRegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownShaderModule(id) => write!(f, "Unknown shader module \"{}\"", id),
+            Self::UnknownPipelineLayout(id) => write!(f, "Unknown pipeline layout \"{}\"", id),
+            Self::InvalidShaderModule(id) => {
+                write!(f, "Shader module \"{}\" failed to compile", id)
+            }
+            Self::CreatePipeline(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for PipelineRegistryError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::CreatePipeline(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Creates pipelines from keys, and reuses them for keys it has seen before.
+///
+/// Shader modules and pipeline layouts are registered under IDs chosen by the user,
+/// which keys can then refer to. This allows keys to be stored, hashed, and loaded
+/// from data files.
+///
+/// Keys implement `Serialize` with the `trace` feature, `Deserialize` with the
+/// `replay` feature, and both with the `pipeline-cache` feature.
+#[derive(Debug, Default)]
+pub struct PipelineRegistry {
+    shader_modules: HashMap<String, ShaderModule>,
+    pipeline_layouts: HashMap<String, PipelineLayout>,
+    render_pipelines: HashMap<RenderPipelineKey, RenderPipeline>,
+    compute_pipelines: HashMap<ComputePipelineKey, ComputePipeline>,
+}
+
+impl PipelineRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a shader module under `id`.
+    ///
+    /// If another module was registered under the same ID, it is returned, and the
+    /// pipelines using it are removed from the registry.
+    pub fn add_shader_module(
+        &mut self,
+        id: impl Into<String>,
+        module: ShaderModule,
+    ) -> Option<ShaderModule> {
+        let id = id.into();
+        let previous = self.shader_modules.insert(id.clone(), module);
+        if previous.is_some() {
+            self.render_pipelines
+                .retain(|key, _| !key.uses_shader_module(&id));
+            self.compute_pipelines
+                .retain(|key, _| key.compute_stage.module != id);
+        }
+        previous
+    }
+
+    /// Get the shader module registered under `id`.
+    pub fn shader_module(&self, id: &str) -> Option<&ShaderModule> {
+        self.shader_modules.get(id)
+    }
+
+    /// Register a pipeline layout under `id`.
+    ///
+    /// If another layout was registered under the same ID, it is returned, and the
+    /// pipelines using it are removed from the registry.
+    pub fn add_pipeline_layout(
+        &mut self,
+        id: impl Into<String>,
+        layout: PipelineLayout,
+    ) -> Option<PipelineLayout> {
+        let id = id.into();
+        let previous = self.pipeline_layouts.insert(id.clone(), layout);
+        if previous.is_some() {
+            self.render_pipelines
+                .retain(|key, _| key.layout.as_ref() != Some(&id));
+            self.compute_pipelines
+                .retain(|key, _| key.layout.as_ref() != Some(&id));
+        }
+        previous
+    }
+
+    /// Get the render pipeline described by `key`, creating it if it isn't in the registry yet.
+    pub fn render_pipeline(
+        &mut self,
+        device: &Device,
+        key: &RenderPipelineKey,
+    ) -> Result<&RenderPipeline, PipelineRegistryError> {
+        if !self.render_pipelines.contains_key(key) {
+            let pipeline = self.create_render_pipeline(device, key)?;
+            self.render_pipelines.insert(key.clone(), pipeline);
+        }
+        Ok(&self.render_pipelines[key])
+    }
+
+    /// Get the compute pipeline described by `key`, creating it if it isn't in the registry yet.
+    pub fn compute_pipeline(
+        &mut self,
+        device: &Device,
+        key: &ComputePipelineKey,
+    ) -> Result<&ComputePipeline, PipelineRegistryError> {
+        if !self.compute_pipelines.contains_key(key) {
+            let pipeline = self.create_compute_pipeline(device, key)?;
+            self.compute_pipelines.insert(key.clone(), pipeline);
+        }
+        Ok(&self.compute_pipelines[key])
+    }
+
+    /// Remove all the pipelines, keeping the registered shader modules and layouts.
+    pub fn clear_pipelines(&mut self) {
+        self.render_pipelines.clear();
+        self.compute_pipelines.clear();
+    }
+
+    fn layout(&self, id: Option<&str>) -> Result<Option<&PipelineLayout>, PipelineRegistryError> {
+        match id {
+            Some(id) => self
+                .pipeline_layouts
+                .get(id)
+                .map(Some)
+                .ok_or_else(|| PipelineRegistryError::UnknownPipelineLayout(id.to_string())),
+            None => Ok(None),
+        }
+    }
+
+    fn stage<'a>(
+        &'a self,
+        key: &'a ProgrammableStageKey,
+        constants: &'a [(&'a str, f64)],
+    ) -> Result<ProgrammableStageDescriptor<'a>, PipelineRegistryError> {
+        let module = self
+            .shader_modules
+            .get(&key.module)
+            .ok_or_else(|| PipelineRegistryError::UnknownShaderModule(key.module.clone()))?;
+        if module.owner.id.is_none() {
+            return Err(PipelineRegistryError::InvalidShaderModule(
+                key.module.clone(),
+            ));
+        }
+        Ok(ProgrammableStageDescriptor {
+            module,
+            entry_point: &key.entry_point,
+            constants,
+        })
+    }
+
+    fn create_render_pipeline(
+        &self,
+        device: &Device,
+        key: &RenderPipelineKey,
+    ) -> Result<RenderPipeline, PipelineRegistryError> {
+        let vertex_constants = borrow_constants(&key.vertex_stage.constants);
+        let fragment_constants = key
+            .fragment_stage
+            .as_ref()
+            .map(|stage| borrow_constants(&stage.constants))
+            .unwrap_or_default();
+        let fragment_stage = match key.fragment_stage {
+            Some(ref stage) => Some(self.stage(stage, &fragment_constants)?),
+            None => None,
+        };
+        let vertex_buffers = key
+            .vertex_buffers
+            .iter()
+            .map(VertexBufferKey::as_descriptor)
+            .collect::<Vec<_>>();
+
+        device
+            .try_create_render_pipeline(&RenderPipelineDescriptor {
+                label: None,
+                layout: self.layout(key.layout.as_deref())?,
+                vertex_stage: self.stage(&key.vertex_stage, &vertex_constants)?,
+                fragment_stage,
+                rasterization_state: key.rasterization_state.clone(),
+                primitive_topology: key.primitive_topology,
+                color_states: &key.color_states,
+                depth_stencil_state: key.depth_stencil_state.clone(),
+                vertex_state: VertexStateDescriptor {
+                    index_format: key.index_format,
+                    vertex_buffers: &vertex_buffers,
+                },
+                sample_count: key.sample_count,
+                sample_mask: key.sample_mask,
+                alpha_to_coverage_enabled: key.alpha_to_coverage_enabled,
+            })
+            .map_err(PipelineRegistryError::CreatePipeline)
+    }
+
+    fn create_compute_pipeline(
+        &self,
+        device: &Device,
+        key: &ComputePipelineKey,
+    ) -> Result<ComputePipeline, PipelineRegistryError> {
+        let compute_constants = borrow_constants(&key.compute_stage.constants);
+        device
+            .try_create_compute_pipeline(&ComputePipelineDescriptor {
+                label: None,
+                layout: self.layout(key.layout.as_deref())?,
+                compute_stage: self.stage(&key.compute_stage, &compute_constants)?,
+            })
+            .map_err(PipelineRegistryError::CreatePipeline)
+    }
+}
+
+/// Borrow owned pipeline-overridable constants, as expected by
+/// [`ProgrammableStageDescriptor::constants`].
+pub(super) fn borrow_constants(constants: &[(String, f64)]) -> Vec<(&str, f64)> {
+    constants
+        .iter()
+        .map(|&(ref key, value)| (key.as_str(), value))
+        .collect()
+}
+
+#[test]
+fn test_keys_compare_float_bits() {
+    use std::collections::hash_map::DefaultHasher;
+
+    fn hash<T: Hash>(value: &T) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+    let stage = |constant| ProgrammableStageKey {
+        module: "shader".to_string(),
+        entry_point: "main".to_string(),
+        constants: vec![("0".to_string(), constant)],
+    };
+    let render = |depth_bias_slope_scale| RenderPipelineKey {
+        layout: None,
+        vertex_stage: stage(1.0),
+        fragment_stage: None,
+        rasterization_state: Some(RasterizationStateDescriptor {
+            depth_bias_slope_scale,
+            ..Default::default()
+        }),
+        primitive_topology: PrimitiveTopology::TriangleList,
+        color_states: Vec::new(),
+        depth_stencil_state: None,
+        index_format: IndexFormat::Uint16,
+        vertex_buffers: Vec::new(),
+        sample_count: 1,
+        sample_mask: !0,
+        alpha_to_coverage_enabled: false,
+    };
+
+    assert_eq!(stage(1.5), stage(1.5));
+    assert_eq!(hash(&stage(1.5)), hash(&stage(1.5)));
+    assert_ne!(stage(1.5), stage(2.5));
+    // Unlike `f64`, NaN equals itself, and zeros of different signs differ.
+    assert_eq!(stage(f64::NAN), stage(f64::NAN));
+    assert_eq!(hash(&stage(f64::NAN)), hash(&stage(f64::NAN)));
+    assert_ne!(stage(0.0), stage(-0.0));
+
+    assert_eq!(render(0.5), render(0.5));
+    assert_eq!(hash(&render(0.5)), hash(&render(0.5)));
+    assert_eq!(render(f32::NAN), render(f32::NAN));
+    assert_eq!(hash(&render(f32::NAN)), hash(&render(f32::NAN)));
+    assert_ne!(render(0.0), render(-0.0));
+}
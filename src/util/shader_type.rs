@@ -0,0 +1,185 @@
+use crate::{Buffer, BufferAddress, Queue};
+
+/// Rules for laying out data in buffers accessed by shaders.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MemoryLayout {
+    /// Layout of uniform buffers. Arrays and structures are aligned to 16 bytes.
+    Std140,
+    /// Layout of storage buffers. Arrays and structures are aligned like their members.
+    Std430,
+}
+
+impl MemoryLayout {
+    /// Returns the alignment of an array whose elements have the given alignment.
+    pub fn array_alignment(self, element_alignment: usize) -> usize {
+        match self {
+            Self::Std140 => element_alignment.max(16),
+            Self::Std430 => element_alignment,
+        }
+    }
+
+    /// Returns the distance in bytes between elements of an array.
+    pub fn array_stride(self, element_size: usize, element_alignment: usize) -> usize {
+        let alignment = self.array_alignment(element_alignment);
+        (element_size + alignment - 1) / alignment * alignment
+    }
+
+    /// Returns the alignment of a structure whose most aligned member has the given alignment.
+    pub fn struct_alignment(self, member_alignment: usize) -> usize {
+        // Structures follow the same rules as arrays.
+        self.array_alignment(member_alignment)
+    }
+}
+
+/// A type that can be written to buffers with the layout expected by shaders.
+///
+/// This is implemented for scalars, and for vectors represented as arrays of 2 to 4 scalars.
+/// Matrices are arrays of column vectors, so `[[f32; 4]; 4]` is a `mat4`.
+///
+/// With the `derive` feature enabled, this can be derived for structs. Fields are then laid
+/// out in order, with the padding required by the layout inserted between them. Array fields
+/// are supported, including arrays of arrays and arrays of structs:
+///
+/// ```
+/// # #[cfg(feature = "derive")] {
+/// use wgpu::util::{MemoryLayout, ShaderType};
+///
+/// #[derive(ShaderType)]
+/// struct Light {
+///     position: [f32; 3],
+///     intensity: f32,
+///     color: [f32; 3],
+/// }
+///
+/// #[derive(ShaderType)]
+/// struct Globals {
+///     view_proj: [[f32; 4]; 4],
+///     lights: [Light; 2],
+///     light_count: u32,
+/// }
+///
+/// assert_eq!(Light::size(MemoryLayout::Std140), 32);
+/// assert_eq!(Globals::size(MemoryLayout::Std140), 144);
+/// # }
+/// ```
+pub trait ShaderType {
+    /// Returns the alignment of this type in the given layout.
+    fn alignment(layout: MemoryLayout) -> usize;
+
+    /// Returns the size of this type in the given layout, including trailing padding
+    /// of structures.
+    fn size(layout: MemoryLayout) -> usize;
+
+    /// Writes this value to the beginning of `out`, in the given layout.
+    ///
+    /// `out` must be at least [`size`](Self::size) bytes long. Padding bytes are left untouched.
+    /// A mapped [`BufferViewMut`](crate::BufferViewMut) can be written to directly.
+    fn write_bytes(&self, layout: MemoryLayout, out: &mut [u8]);
+
+    /// Returns this value in the given layout, with padding bytes set to zero.
+    fn to_bytes(&self, layout: MemoryLayout) -> Vec<u8> {
+        let mut bytes = vec![0; Self::size(layout)];
+        self.write_bytes(layout, &mut bytes);
+        bytes
+    }
+
+    /// Schedules writing this value to a buffer at the given offset, in the given layout.
+    ///
+    /// See [`Queue::write_buffer`] for details.
+    fn write_buffer(
+        &self,
+        queue: &Queue,
+        buffer: &Buffer,
+        offset: BufferAddress,
+        layout: MemoryLayout,
+    ) {
+        queue.write_buffer(buffer, offset, &self.to_bytes(layout));
+    }
+}
+
+macro_rules! impl_scalar {
+    ($($ty:ty => |$value:ident| $bytes:expr,)*) => {
+        $(
+            impl ShaderType for $ty {
+                fn alignment(_layout: MemoryLayout) -> usize {
+                    4
+                }
+
+                fn size(_layout: MemoryLayout) -> usize {
+                    4
+                }
+
+                fn write_bytes(&self, _layout: MemoryLayout, out: &mut [u8]) {
+                    let $value = *self;
+                    out[..4].copy_from_slice(&$bytes);
+                }
+            }
+
+            impl ShaderType for [$ty; 2] {
+                fn alignment(_layout: MemoryLayout) -> usize {
+                    8
+                }
+
+                fn size(_layout: MemoryLayout) -> usize {
+                    8
+                }
+
+                fn write_bytes(&self, layout: MemoryLayout, out: &mut [u8]) {
+                    for (i, component) in self.iter().enumerate() {
+                        component.write_bytes(layout, &mut out[i * 4..]);
+                    }
+                }
+            }
+
+            impl ShaderType for [$ty; 3] {
+                fn alignment(_layout: MemoryLayout) -> usize {
+                    16
+                }
+
+                fn size(_layout: MemoryLayout) -> usize {
+                    12
+                }
+
+                fn write_bytes(&self, layout: MemoryLayout, out: &mut [u8]) {
+                    for (i, component) in self.iter().enumerate() {
+                        component.write_bytes(layout, &mut out[i * 4..]);
+                    }
+                }
+            }
+
+            impl ShaderType for [$ty; 4] {
+                fn alignment(_layout: MemoryLayout) -> usize {
+                    16
+                }
+
+                fn size(_layout: MemoryLayout) -> usize {
+                    16
+                }
+
+                fn write_bytes(&self, layout: MemoryLayout, out: &mut [u8]) {
+                    for (i, component) in self.iter().enumerate() {
+                        component.write_bytes(layout, &mut out[i * 4..]);
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_scalar! {
+    f32 => |value| value.to_le_bytes(),
+    i32 => |value| value.to_le_bytes(),
+    u32 => |value| value.to_le_bytes(),
+    bool => |value| (value as u32).to_le_bytes(),
+}
+
+#[test]
+fn test_array_layout() {
+    // `vec3` arrays are padded to 16 bytes per element in both layouts.
+    assert_eq!(MemoryLayout::Std140.array_stride(12, 16), 16);
+    assert_eq!(MemoryLayout::Std430.array_stride(12, 16), 16);
+    // Scalar arrays are only tightly packed in std430.
+    assert_eq!(MemoryLayout::Std140.array_stride(4, 4), 16);
+    assert_eq!(MemoryLayout::Std430.array_stride(4, 4), 4);
+    assert_eq!(MemoryLayout::Std430.array_stride(8, 8), 8);
+}
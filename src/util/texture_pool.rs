@@ -0,0 +1,243 @@
+use crate::{
+    Buffer, BufferDescriptor, BufferUsage, CommandEncoder, Device, MapMode, Texture,
+    TextureDescriptor,
+};
+use futures::{future::join_all, FutureExt};
+use parking_lot::Mutex;
+use std::{
+    future::Future,
+    ops::Deref,
+    sync::{mpsc, Arc},
+};
+
+/// State shared between a pool and the textures it handed out.
+#[derive(Debug, Default)]
+struct Shared {
+    /// Textures dropped since the last `end_frame`.
+    released: Vec<Texture>,
+    in_use: usize,
+}
+
+/// Textures released during a frame, along with the buffer signaling that the GPU
+/// is done with the frame.
+struct Batch {
+    fence: Buffer,
+    textures: Vec<Texture>,
+}
+
+struct Idle {
+    texture: Texture,
+    last_used_frame: u64,
+}
+
+/// Statistics of a [`TexturePool`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TexturePoolStats {
+    /// Number of textures handed out and not dropped yet.
+    pub in_use: usize,
+    /// Number of dropped textures that the GPU may still be using.
+    pub pending: usize,
+    /// Number of textures ready to be reused.
+    pub idle: usize,
+    /// Number of textures created by the pool.
+    pub created: u64,
+    /// Number of acquisitions that reused a texture instead of creating one.
+    pub reused: u64,
+    /// Number of idle textures destroyed after being unused for too long.
+    pub evicted: u64,
+}
+
+/// Texture handed out by a [`TexturePool`], which goes back to the pool when dropped.
+#[derive(Debug)]
+pub struct PooledTexture {
+    texture: Option<Texture>,
+    shared: Arc<Mutex<Shared>>,
+}
+
+impl Deref for PooledTexture {
+    type Target = Texture;
+
+    fn deref(&self) -> &Texture {
+        self.texture.as_ref().unwrap()
+    }
+}
+
+impl Drop for PooledTexture {
+    fn drop(&mut self) {
+        let texture = self.texture.take().unwrap();
+        let mut shared = self.shared.lock();
+        shared.in_use -= 1;
+        if !texture.is_destroyed() {
+            shared.released.push(texture);
+        }
+    }
+}
+
+/// Pool of render targets and other transient textures, reused across frames.
+///
+/// Post-processing chains can acquire their intermediate textures from the pool every frame,
+/// or when the window is resized, without creating new ones each time.
+///
+/// Using a texture pool generally goes as follows:
+/// - Acquire textures with `acquire`, and drop them once the commands using them are recorded.
+/// - Call `end_frame` with the last encoder of the frame.
+/// - Submit all command encoders of the frame.
+/// - Call `recall`.
+///
+/// Dropped textures are only reused once the GPU is done with the frame they were dropped in,
+/// which `end_frame` tracks with a small buffer written by the encoder.
+pub struct TexturePool {
+    max_unused_frames: u64,
+    frame: u64,
+    shared: Arc<Mutex<Shared>>,
+    /// Batches of the frames ended since the last `recall`.
+    closed_batches: Vec<Batch>,
+    /// Number of textures in batches mapped by `recall` and not received yet.
+    in_flight: usize,
+    idle: Vec<Idle>,
+    free_fences: Vec<Buffer>,
+    sender: mpsc::Sender<Batch>,
+    receiver: mpsc::Receiver<Batch>,
+    created: u64,
+    reused: u64,
+    evicted: u64,
+}
+
+impl TexturePool {
+    /// Creates an empty pool.
+    ///
+    /// Idle textures that aren't acquired for `max_unused_frames` frames are destroyed.
+    pub fn new(max_unused_frames: u32) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        TexturePool {
+            max_unused_frames: max_unused_frames as u64,
+            frame: 0,
+            shared: Arc::default(),
+            closed_batches: Vec::new(),
+            in_flight: 0,
+            idle: Vec::new(),
+            free_fences: Vec::new(),
+            sender,
+            receiver,
+            created: 0,
+            reused: 0,
+            evicted: 0,
+        }
+    }
+
+    /// Makes the textures of the batches the GPU is done with available for reuse.
+    fn receive(&mut self) {
+        while let Ok(batch) = self.receiver.try_recv() {
+            batch.fence.unmap();
+            self.in_flight -= batch.textures.len();
+            let frame = self.frame;
+            self.idle
+                .extend(batch.textures.into_iter().map(|texture| Idle {
+                    texture,
+                    last_used_frame: frame,
+                }));
+            self.free_fences.push(batch.fence);
+        }
+    }
+
+    /// Returns an idle texture matching `desc`, or creates one.
+    ///
+    /// The label of `desc` is only used for new textures, and is ignored when looking for
+    /// a matching idle texture.
+    pub fn acquire(&mut self, device: &Device, desc: &TextureDescriptor) -> PooledTexture {
+        self.receive();
+        let key = TextureDescriptor {
+            label: None,
+            ..desc.clone()
+        };
+        let texture = match self
+            .idle
+            .iter()
+            .position(|idle| *idle.texture.descriptor() == key)
+        {
+            Some(index) => {
+                self.reused += 1;
+                self.idle.swap_remove(index).texture
+            }
+            None => {
+                self.created += 1;
+                device.create_texture(desc)
+            }
+        };
+
+        self.shared.lock().in_use += 1;
+        PooledTexture {
+            texture: Some(texture),
+            shared: Arc::clone(&self.shared),
+        }
+    }
+
+    /// Closes the current frame, and evicts the textures that have been idle for too long.
+    ///
+    /// The textures dropped during the frame are kept until the GPU is done with `encoder`,
+    /// which must be the last encoder of the frame to be submitted.
+    pub fn end_frame(&mut self, device: &Device, encoder: &mut CommandEncoder) {
+        let textures = std::mem::take(&mut self.shared.lock().released);
+        if !textures.is_empty() {
+            let fence = self.free_fences.pop().unwrap_or_else(|| {
+                device.create_buffer(&BufferDescriptor {
+                    label: Some("texture pool fence"),
+                    size: crate::COPY_BUFFER_ALIGNMENT,
+                    usage: BufferUsage::MAP_READ | BufferUsage::COPY_DST,
+                    mapped_at_creation: false,
+                })
+            });
+            encoder.clear_buffer(&fence, ..);
+            self.closed_batches.push(Batch { fence, textures });
+        }
+
+        let (frame, max_unused_frames) = (self.frame, self.max_unused_frames);
+        let evicted_before = self.idle.len();
+        self.idle
+            .retain(|idle| frame - idle.last_used_frame < max_unused_frames);
+        self.evicted += (evicted_before - self.idle.len()) as u64;
+        self.frame += 1;
+    }
+
+    /// Starts waiting for the GPU to be done with the frames ended since the last call,
+    /// so that their textures can be reused.
+    ///
+    /// This has to be called after the encoders passed to `end_frame` are submitted!
+    pub fn recall(&mut self) -> impl Future<Output = ()> + Send {
+        self.receive();
+
+        let sender_template = &self.sender;
+        let in_flight = &mut self.in_flight;
+        join_all(self.closed_batches.drain(..).map(|batch| {
+            *in_flight += batch.textures.len();
+            let sender = sender_template.clone();
+            batch
+                .fence
+                .slice(..)
+                .map_async(MapMode::Read)
+                .inspect(move |_| {
+                    // The pool may have been dropped in the meantime.
+                    let _ = sender.send(batch);
+                })
+        }))
+        .map(|_| ())
+    }
+
+    /// Returns the statistics of the pool.
+    pub fn stats(&self) -> TexturePoolStats {
+        let shared = self.shared.lock();
+        let closed: usize = self
+            .closed_batches
+            .iter()
+            .map(|batch| batch.textures.len())
+            .sum();
+        TexturePoolStats {
+            in_use: shared.in_use,
+            pending: shared.released.len() + closed + self.in_flight,
+            idle: self.idle.len(),
+            created: self.created,
+            reused: self.reused,
+            evicted: self.evicted,
+        }
+    }
+}
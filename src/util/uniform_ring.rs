@@ -0,0 +1,187 @@
+use super::{MemoryLayout, ShaderType, StagingBelt};
+use crate::{
+    BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor,
+    BindGroupLayoutEntry, BindingResource, BindingType, Buffer, BufferAddress, BufferDescriptor,
+    BufferSize, BufferUsage, CommandEncoder, Device, DynamicOffset, ShaderStage,
+    BIND_BUFFER_ALIGNMENT, COPY_BUFFER_ALIGNMENT,
+};
+use std::future::Future;
+
+/// Describes a [`UniformRing`].
+#[derive(Clone, Debug)]
+pub struct UniformRingDescriptor<'a> {
+    /// Debug label of the ring. This will show up in graphics debuggers for easy identification.
+    pub label: Option<&'a str>,
+    /// Which shader stages can see the uniforms.
+    pub visibility: ShaderStage,
+    /// Size of the uniform binding, which must be at least the size of every pushed value.
+    pub binding_size: BufferSize,
+    /// Initial size of the uniform buffer. It grows when a frame pushes more data than fits.
+    pub capacity: BufferAddress,
+}
+
+/// Ring of per-draw uniforms, bound with dynamic offsets.
+///
+/// All values pushed during a frame are stored in a single uniform buffer, so that one
+/// bind group covers all of them, each draw selecting its own value with a dynamic offset.
+/// The bind group layout has a single dynamic uniform buffer at binding 0.
+///
+/// Using a uniform ring generally goes as follows:
+/// - Push the values of each draw with `push`, keeping the returned offsets.
+/// - Call `upload` with the encoder of the frame.
+/// - Record draws, calling `set_bind_group` with `bind_group` and the offset of each draw.
+/// - Submit the encoder, then call `recall`.
+///
+/// Values are laid out with [`MemoryLayout::Std140`]. Each upload overwrites the uniform buffer
+/// from the start, which is safe since copies execute after the draws of previous submissions.
+pub struct UniformRing {
+    label: Option<String>,
+    binding_size: BufferSize,
+    layout: BindGroupLayout,
+    buffer: Buffer,
+    bind_group: BindGroup,
+    capacity: BufferAddress,
+    data: Vec<u8>,
+    belt: StagingBelt,
+}
+
+impl UniformRing {
+    /// Creates a new uniform ring.
+    pub fn new(device: &Device, desc: &UniformRingDescriptor) -> Self {
+        let layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: desc.label,
+            entries: &[BindGroupLayoutEntry {
+                binding: 0,
+                visibility: desc.visibility,
+                ty: BindingType::UniformBuffer {
+                    dynamic: true,
+                    min_binding_size: Some(desc.binding_size),
+                },
+                count: None,
+            }],
+        });
+        let capacity = desc.capacity.max(desc.binding_size.get());
+        let (buffer, bind_group) =
+            Self::create_buffer(device, desc.label, &layout, desc.binding_size, capacity);
+        UniformRing {
+            label: desc.label.map(String::from),
+            binding_size: desc.binding_size,
+            layout,
+            buffer,
+            bind_group,
+            capacity,
+            data: Vec::new(),
+            belt: StagingBelt::new(capacity),
+        }
+    }
+
+    fn create_buffer(
+        device: &Device,
+        label: Option<&str>,
+        layout: &BindGroupLayout,
+        binding_size: BufferSize,
+        capacity: BufferAddress,
+    ) -> (Buffer, BindGroup) {
+        let buffer = device.create_buffer(&BufferDescriptor {
+            label,
+            size: capacity,
+            usage: BufferUsage::UNIFORM | BufferUsage::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label,
+            layout,
+            entries: &[BindGroupEntry {
+                binding: 0,
+                resource: BindingResource::Buffer {
+                    buffer: &buffer,
+                    offset: 0,
+                    size: Some(binding_size),
+                },
+            }],
+        });
+        (buffer, bind_group)
+    }
+
+    /// Adds a value for this frame, returning its dynamic offset in the bind group.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is larger than the binding size of the ring.
+    pub fn push<T: ShaderType>(&mut self, value: &T) -> DynamicOffset {
+        let size = T::size(MemoryLayout::Std140);
+        assert!(
+            size as BufferAddress <= self.binding_size.get(),
+            "Value of {} bytes is larger than the binding size of the ring ({} bytes)",
+            size,
+            self.binding_size
+        );
+
+        let offset = (self.data.len() as BufferAddress + BIND_BUFFER_ALIGNMENT - 1)
+            / BIND_BUFFER_ALIGNMENT
+            * BIND_BUFFER_ALIGNMENT;
+        self.data.resize(offset as usize + size, 0);
+        value.write_bytes(MemoryLayout::Std140, &mut self.data[offset as usize..]);
+        offset as DynamicOffset
+    }
+
+    /// Schedules the upload of all the values pushed since the last upload.
+    ///
+    /// If they don't fit in the uniform buffer, a larger one is created along with
+    /// a new bind group, so [`bind_group`](Self::bind_group) must be called after this.
+    pub fn upload(&mut self, encoder: &mut CommandEncoder, device: &Device) {
+        if self.data.is_empty() {
+            return;
+        }
+
+        // The binding at the last offset must fit in the buffer.
+        let last_offset =
+            (self.data.len() as BufferAddress - 1) / BIND_BUFFER_ALIGNMENT * BIND_BUFFER_ALIGNMENT;
+        let required = last_offset + self.binding_size.get();
+        if required > self.capacity {
+            self.capacity = required.next_power_of_two();
+            let (buffer, bind_group) = Self::create_buffer(
+                device,
+                self.label.as_deref(),
+                &self.layout,
+                self.binding_size,
+                self.capacity,
+            );
+            self.buffer = buffer;
+            self.bind_group = bind_group;
+        }
+
+        let size = (self.data.len() as BufferAddress + COPY_BUFFER_ALIGNMENT - 1)
+            / COPY_BUFFER_ALIGNMENT
+            * COPY_BUFFER_ALIGNMENT;
+        self.data.resize(size as usize, 0);
+        self.belt
+            .write_buffer(
+                encoder,
+                &self.buffer,
+                0,
+                BufferSize::new(size).unwrap(),
+                device,
+            )
+            .copy_from_slice(&self.data);
+        self.belt.finish();
+        self.data.clear();
+    }
+
+    /// Recalls the staging memory used by the upload, to be reused by the next frame.
+    ///
+    /// This has to be called after the encoder passed to `upload` is submitted.
+    pub fn recall(&mut self) -> impl Future<Output = ()> + Send {
+        self.belt.recall()
+    }
+
+    /// Returns the layout of the bind group, to create pipeline layouts with.
+    pub fn bind_group_layout(&self) -> &BindGroupLayout {
+        &self.layout
+    }
+
+    /// Returns the bind group of the ring.
+    pub fn bind_group(&self) -> &BindGroup {
+        &self.bind_group
+    }
+}
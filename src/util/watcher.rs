@@ -0,0 +1,519 @@
+use super::registry::{borrow_constants, VertexBufferKey};
+use crate::{
+    ColorStateDescriptor, ComputePipeline, ComputePipelineDescriptor, CreatePipelineError,
+    CreateShaderModuleError, DepthStencilStateDescriptor, Device, IndexFormat, PipelineLayout,
+    PrimitiveTopology, ProgrammableStageDescriptor, RasterizationStateDescriptor, RenderPipeline,
+    RenderPipelineDescriptor, ShaderModule, ShaderModuleSource, VertexStateDescriptor,
+};
+use std::{
+    borrow::Cow,
+    error::Error,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+
+const SPIRV_MAGIC_NUMBER: u32 = 0x0723_0203;
+
+/// Handle to a shader file registered with [`ShaderWatcher::watch_shader`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WatchedShader(usize);
+
+/// Handle to a render pipeline registered with [`ShaderWatcher::add_render_pipeline`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WatchedRenderPipeline(usize);
+
+/// Handle to a compute pipeline registered with [`ShaderWatcher::add_compute_pipeline`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WatchedComputePipeline(usize);
+
+/// Describes a programmable pipeline stage backed by a watched shader.
+#[derive(Clone, Debug)]
+pub struct WatchedStageDescriptor {
+    /// The watched shader providing the module for this stage.
+    pub shader: WatchedShader,
+    /// The name of the entry point in the compiled shader.
+    pub entry_point: String,
+    /// Values of pipeline-overridable constants, see [`ProgrammableStageDescriptor::constants`].
+    pub constants: Vec<(String, f64)>,
+}
+
+/// Describes a render pipeline that is rebuilt whenever one of its shaders changes.
+///
+/// Mirrors [`RenderPipelineDescriptor`], but owns all of its data.
+#[derive(Clone, Debug)]
+pub struct WatchedRenderPipelineDescriptor {
+    /// Debug label of the pipeline.
+    pub label: Option<String>,
+    /// The layout of bind groups for this pipeline.
+    pub layout: Option<Arc<PipelineLayout>>,
+    /// The vertex stage and its entry point.
+    pub vertex_stage: WatchedStageDescriptor,
+    /// The fragment stage and its entry point, if any.
+    pub fragment_stage: Option<WatchedStageDescriptor>,
+    /// The rasterization process for this pipeline.
+    pub rasterization_state: Option<RasterizationStateDescriptor>,
+    /// The primitive topology used to interpret vertices.
+    pub primitive_topology: PrimitiveTopology,
+    /// The effect of draw calls on the color aspect of the output target.
+    pub color_states: Vec<ColorStateDescriptor>,
+    /// The effect of draw calls on the depth and stencil aspects of the output target, if any.
+    pub depth_stencil_state: Option<DepthStencilStateDescriptor>,
+    /// The format of any index buffers used with this pipeline.
+    pub index_format: IndexFormat,
+    /// The format of any vertex buffers used with this pipeline.
+    pub vertex_buffers: Vec<VertexBufferKey>,
+    /// The number of samples calculated per pixel (for MSAA).
+    pub sample_count: u32,
+    /// Bitmask that restricts the samples of a pixel modified by this pipeline.
+    pub sample_mask: u32,
+    /// Enables alpha to coverage, see [`RenderPipelineDescriptor::alpha_to_coverage_enabled`].
+    pub alpha_to_coverage_enabled: bool,
+}
+
+/// Describes a compute pipeline that is rebuilt whenever its shader changes.
+#[derive(Clone, Debug)]
+pub struct WatchedComputePipelineDescriptor {
+    /// Debug label of the pipeline.
+    pub label: Option<String>,
+    /// The layout of bind groups for this pipeline.
+    pub layout: Option<Arc<PipelineLayout>>,
+    /// The compute stage and its entry point.
+    pub compute_stage: WatchedStageDescriptor,
+}
+
+/// Error produced while (re)loading a watched shader.
+#[derive(Debug)]
+pub enum ShaderWatcherError {
+    /// The shader file could not be read.
+    Io {
+        /// Path of the shader file.
+        path: PathBuf,
+        /// Underlying I/O error.
+        error: io::Error,
+    },
+    /// The file is not a shader source the watcher can compile.
+    InvalidSource {
+        /// Path of the shader file.
+        path: PathBuf,
+        /// Why the source was rejected.
+        reason: &'static str,
+    },
+    /// The shader failed to compile.
+    Compilation {
+        /// Path of the shader file.
+        path: PathBuf,
+        /// Error reported by the device.
+        error: CreateShaderModuleError,
+    },
+    /// A pipeline failed to be created from the shaders, for example because an
+    /// entry point was renamed or the interface of a stage changed.
+    Pipeline {
+        /// Debug label of the pipeline.
+        label: Option<String>,
+        /// Error reported by the device.
+        error: CreatePipelineError,
+    },
+}
+
+impl fmt::Display for ShaderWatcherError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { path, error } => write!(f, "Unable to read {}: {}", path.display(), error),
+            Self::InvalidSource { path, reason } => write!(f, "{}: {}", path.display(), reason),
+            Self::Compilation { path, error } => write!(f, "{}: {}", path.display(), error),
+            Self::Pipeline { label, error } => match label {
+                Some(label) => write!(f, "Pipeline {:?}: {}", label, error),
+                None => write!(f, "{}", error),
+            },
+        }
+    }
+}
+
+impl Error for ShaderWatcherError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io { error, .. } => Some(error),
+            Self::InvalidSource { .. } => None,
+            Self::Compilation { error, .. } => Some(error),
+            Self::Pipeline { error, .. } => Some(error),
+        }
+    }
+}
+
+struct ShaderEntry {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    module: ShaderModule,
+}
+
+struct RenderPipelineEntry {
+    desc: WatchedRenderPipelineDescriptor,
+    pipeline: RenderPipeline,
+}
+
+struct ComputePipelineEntry {
+    desc: WatchedComputePipelineDescriptor,
+    pipeline: ComputePipeline,
+}
+
+/// Reloads shaders from disk when they change, and rebuilds the pipelines using them.
+///
+/// Shader files are picked by extension: `.spv` for SPIR-V binaries and `.wgsl` for WGSL.
+/// GLSL has to be compiled to SPIR-V beforehand.
+///
+/// Using a shader watcher generally goes as follows:
+/// - Register shader files using `watch_shader`.
+/// - Register pipelines built from them using `add_render_pipeline` and `add_compute_pipeline`.
+/// - Call `poll` once per frame, and fetch the pipelines with `render_pipeline` and
+///   `compute_pipeline` when recording.
+///
+/// If a changed shader fails to compile, or a pipeline fails to be rebuilt from it,
+/// the previous module or pipeline is kept and the error is returned from `poll`.
+pub struct ShaderWatcher {
+    shaders: Vec<ShaderEntry>,
+    render_pipelines: Vec<RenderPipelineEntry>,
+    compute_pipelines: Vec<ComputePipelineEntry>,
+}
+
+impl ShaderWatcher {
+    /// Create a new shader watcher with nothing registered.
+    pub fn new() -> Self {
+        ShaderWatcher {
+            shaders: Vec::new(),
+            render_pipelines: Vec::new(),
+            compute_pipelines: Vec::new(),
+        }
+    }
+
+    /// Load the shader at `path` and start watching it for changes.
+    pub fn watch_shader<P: AsRef<Path>>(
+        &mut self,
+        device: &Device,
+        path: P,
+    ) -> Result<WatchedShader, ShaderWatcherError> {
+        let path = path.as_ref().to_path_buf();
+        let modified = fs::metadata(&path).and_then(|meta| meta.modified()).ok();
+        let module = load_shader(device, &path)?;
+        self.shaders.push(ShaderEntry {
+            path,
+            modified,
+            module,
+        });
+        Ok(WatchedShader(self.shaders.len() - 1))
+    }
+
+    /// Create a render pipeline that is rebuilt whenever one of its shaders changes.
+    pub fn add_render_pipeline(
+        &mut self,
+        device: &Device,
+        desc: WatchedRenderPipelineDescriptor,
+    ) -> Result<WatchedRenderPipeline, ShaderWatcherError> {
+        let pipeline = create_render_pipeline(&self.shaders, device, &desc)?;
+        self.render_pipelines
+            .push(RenderPipelineEntry { desc, pipeline });
+        Ok(WatchedRenderPipeline(self.render_pipelines.len() - 1))
+    }
+
+    /// Create a compute pipeline that is rebuilt whenever its shader changes.
+    pub fn add_compute_pipeline(
+        &mut self,
+        device: &Device,
+        desc: WatchedComputePipelineDescriptor,
+    ) -> Result<WatchedComputePipeline, ShaderWatcherError> {
+        let pipeline = create_compute_pipeline(&self.shaders, device, &desc)?;
+        self.compute_pipelines
+            .push(ComputePipelineEntry { desc, pipeline });
+        Ok(WatchedComputePipeline(self.compute_pipelines.len() - 1))
+    }
+
+    /// Get the latest successfully compiled module of a watched shader.
+    pub fn shader_module(&self, shader: WatchedShader) -> &ShaderModule {
+        &self.shaders[shader.0].module
+    }
+
+    /// Get the latest version of a watched render pipeline.
+    pub fn render_pipeline(&self, pipeline: WatchedRenderPipeline) -> &RenderPipeline {
+        &self.render_pipelines[pipeline.0].pipeline
+    }
+
+    /// Get the latest version of a watched compute pipeline.
+    pub fn compute_pipeline(&self, pipeline: WatchedComputePipeline) -> &ComputePipeline {
+        &self.compute_pipelines[pipeline.0].pipeline
+    }
+
+    /// Check the watched files for changes, recompile the changed shaders and rebuild
+    /// the pipelines that use them.
+    ///
+    /// Returns the errors of shaders that failed to reload, and of pipelines that failed
+    /// to be rebuilt. A file is only reloaded again after it changes on disk once more.
+    pub fn poll(&mut self, device: &Device) -> Vec<ShaderWatcherError> {
+        let mut errors = Vec::new();
+        let mut reloaded = Vec::new();
+
+        for (index, shader) in self.shaders.iter_mut().enumerate() {
+            // The file may be missing for a moment while an editor saves it,
+            // so only act once its metadata can be read again.
+            let modified = match fs::metadata(&shader.path).and_then(|meta| meta.modified()) {
+                Ok(modified) => Some(modified),
+                Err(_) => continue,
+            };
+            if modified == shader.modified {
+                continue;
+            }
+            shader.modified = modified;
+
+            match load_shader(device, &shader.path) {
+                Ok(module) => {
+                    shader.module = module;
+                    reloaded.push(WatchedShader(index));
+                }
+                Err(error) => errors.push(error),
+            }
+        }
+
+        if !reloaded.is_empty() {
+            let shaders = &self.shaders;
+            for entry in self.render_pipelines.iter_mut() {
+                let desc = &entry.desc;
+                let uses_reloaded = reloaded.contains(&desc.vertex_stage.shader)
+                    || desc
+                        .fragment_stage
+                        .as_ref()
+                        .map_or(false, |stage| reloaded.contains(&stage.shader));
+                if uses_reloaded {
+                    match create_render_pipeline(shaders, device, desc) {
+                        Ok(pipeline) => entry.pipeline = pipeline,
+                        Err(error) => errors.push(error),
+                    }
+                }
+            }
+            for entry in self.compute_pipelines.iter_mut() {
+                if reloaded.contains(&entry.desc.compute_stage.shader) {
+                    match create_compute_pipeline(shaders, device, &entry.desc) {
+                        Ok(pipeline) => entry.pipeline = pipeline,
+                        Err(error) => errors.push(error),
+                    }
+                }
+            }
+        }
+
+        errors
+    }
+}
+
+impl Default for ShaderWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for ShaderWatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShaderWatcher")
+            .field(
+                "shaders",
+                &self.shaders.iter().map(|s| &s.path).collect::<Vec<_>>(),
+            )
+            .field("render_pipelines", &self.render_pipelines.len())
+            .field("compute_pipelines", &self.compute_pipelines.len())
+            .finish()
+    }
+}
+
+fn load_shader(device: &Device, path: &Path) -> Result<ShaderModule, ShaderWatcherError> {
+    let invalid = |reason| ShaderWatcherError::InvalidSource {
+        path: path.to_path_buf(),
+        reason,
+    };
+
+    let data = fs::read(path).map_err(|error| ShaderWatcherError::Io {
+        path: path.to_path_buf(),
+        error,
+    })?;
+    let source = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("spv") => {
+            // `make_spirv` panics on malformed input, so check it here first.
+            if data.len() < 4 || data.len() % 4 != 0 {
+                return Err(invalid("SPIR-V size is not a multiple of 4"));
+            }
+            if u32::from_ne_bytes([data[0], data[1], data[2], data[3]]) != SPIRV_MAGIC_NUMBER {
+                return Err(invalid("SPIR-V magic number is missing"));
+            }
+            super::make_spirv(&data)
+        }
+        Some("wgsl") => match std::str::from_utf8(&data) {
+            Ok(code) => ShaderModuleSource::Wgsl(Cow::Borrowed(code)),
+            Err(_) => return Err(invalid("WGSL source is not valid UTF-8")),
+        },
+        Some("vert") | Some("frag") | Some("comp") | Some("glsl") => {
+            return Err(invalid("GLSL is not supported, compile it to SPIR-V first"))
+        }
+        _ => return Err(invalid("unknown shader file extension")),
+    };
+
+    // Pipelines may override constants of any watched shader.
+    let module = device.create_specializable_shader_module(source);
+    if module.compilation_info.has_errors() {
+        return Err(ShaderWatcherError::Compilation {
+            path: path.to_path_buf(),
+            error: CreateShaderModuleError {
+                info: module.compilation_info.clone(),
+            },
+        });
+    }
+    Ok(module)
+}
+
+fn pipeline_error(label: &Option<String>, error: CreatePipelineError) -> ShaderWatcherError {
+    ShaderWatcherError::Pipeline {
+        label: label.clone(),
+        error,
+    }
+}
+
+fn map_stage<'a>(
+    shaders: &'a [ShaderEntry],
+    stage: &'a WatchedStageDescriptor,
+    constants: &'a [(&'a str, f64)],
+) -> ProgrammableStageDescriptor<'a> {
+    ProgrammableStageDescriptor {
+        module: &shaders[stage.shader.0].module,
+        entry_point: &stage.entry_point,
+        constants,
+    }
+}
+
+fn create_render_pipeline(
+    shaders: &[ShaderEntry],
+    device: &Device,
+    desc: &WatchedRenderPipelineDescriptor,
+) -> Result<RenderPipeline, ShaderWatcherError> {
+    let vertex_buffers = desc
+        .vertex_buffers
+        .iter()
+        .map(VertexBufferKey::as_descriptor)
+        .collect::<Vec<_>>();
+    let vertex_constants = borrow_constants(&desc.vertex_stage.constants);
+    let fragment_constants = desc
+        .fragment_stage
+        .as_ref()
+        .map(|stage| borrow_constants(&stage.constants))
+        .unwrap_or_default();
+
+    device
+        .try_create_render_pipeline(&RenderPipelineDescriptor {
+            label: desc.label.as_deref(),
+            layout: desc.layout.as_deref(),
+            vertex_stage: map_stage(shaders, &desc.vertex_stage, &vertex_constants),
+            fragment_stage: desc
+                .fragment_stage
+                .as_ref()
+                .map(|stage| map_stage(shaders, stage, &fragment_constants)),
+            rasterization_state: desc.rasterization_state.clone(),
+            primitive_topology: desc.primitive_topology,
+            color_states: &desc.color_states,
+            depth_stencil_state: desc.depth_stencil_state.clone(),
+            vertex_state: VertexStateDescriptor {
+                index_format: desc.index_format,
+                vertex_buffers: &vertex_buffers,
+            },
+            sample_count: desc.sample_count,
+            sample_mask: desc.sample_mask,
+            alpha_to_coverage_enabled: desc.alpha_to_coverage_enabled,
+        })
+        .map_err(|error| pipeline_error(&desc.label, error))
+}
+
+fn create_compute_pipeline(
+    shaders: &[ShaderEntry],
+    device: &Device,
+    desc: &WatchedComputePipelineDescriptor,
+) -> Result<ComputePipeline, ShaderWatcherError> {
+    let compute_constants = borrow_constants(&desc.compute_stage.constants);
+    device
+        .try_create_compute_pipeline(&ComputePipelineDescriptor {
+            label: desc.label.as_deref(),
+            layout: desc.layout.as_deref(),
+            compute_stage: map_stage(shaders, &desc.compute_stage, &compute_constants),
+        })
+        .map_err(|error| pipeline_error(&desc.label, error))
+}
+
+#[test]
+fn test_reload_keeps_previous_on_error() {
+    const SHADER: &str =
+        "fn main() -> void {\n  return;\n}\nentry_point compute as \"main\" = main;\n";
+
+    let instance = crate::Instance::new(crate::BackendBit::PRIMARY);
+    let adapter =
+        match futures::executor::block_on(instance.request_adapter(&crate::RequestAdapterOptions {
+            power_preference: crate::PowerPreference::Default,
+            compatible_surface: None,
+        })) {
+            Some(adapter) => adapter,
+            // Nothing to test against without a GPU.
+            None => return,
+        };
+    let (device, _queue) = futures::executor::block_on(adapter.request_device(
+        &crate::DeviceDescriptor {
+            features: crate::Features::empty(),
+            limits: crate::Limits::default(),
+            shader_validation: true,
+        },
+        None,
+    ))
+    .unwrap();
+
+    let path = std::env::temp_dir().join(format!("wgpu-watcher-{}.wgsl", std::process::id()));
+    fs::write(&path, SHADER).unwrap();
+    let mut watcher = ShaderWatcher::new();
+    let shader = watcher.watch_shader(&device, &path).unwrap();
+    let pipeline = watcher
+        .add_compute_pipeline(
+            &device,
+            WatchedComputePipelineDescriptor {
+                label: None,
+                layout: None,
+                compute_stage: WatchedStageDescriptor {
+                    shader,
+                    entry_point: "main".to_string(),
+                    constants: Vec::new(),
+                },
+            },
+        )
+        .unwrap();
+    // Modification times may be too coarse to see quick writes, so force reloads.
+    let reload = |watcher: &mut ShaderWatcher, source: &str| {
+        fs::write(&path, source).unwrap();
+        watcher.shaders[shader.0].modified = None;
+        watcher.poll(&device)
+    };
+    let same_owner = |a: &ComputePipeline, b: &ComputePipeline| Arc::ptr_eq(&a.owner, &b.owner);
+
+    // Parsing fails, so both the module and the pipeline are kept.
+    let module = watcher.shader_module(shader).clone();
+    let previous = watcher.compute_pipeline(pipeline).clone();
+    let errors = reload(&mut watcher, "fn main(");
+    assert!(matches!(
+        errors[..],
+        [ShaderWatcherError::Compilation { .. }]
+    ));
+    assert!(Arc::ptr_eq(
+        &module.owner,
+        &watcher.shader_module(shader).owner
+    ));
+    assert!(same_owner(&previous, watcher.compute_pipeline(pipeline)));
+
+    // The module is valid but the entry point is gone, so the pipeline is kept.
+    let errors = reload(&mut watcher, &SHADER.replace("\"main\"", "\"other\""));
+    assert!(matches!(errors[..], [ShaderWatcherError::Pipeline { .. }]));
+    assert!(same_owner(&previous, watcher.compute_pipeline(pipeline)));
+
+    let errors = reload(&mut watcher, SHADER);
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert!(!same_owner(&previous, watcher.compute_pipeline(pipeline)));
+
+    let _ = fs::remove_file(&path);
+}
@@ -0,0 +1,50 @@
+//! Derive macros for wgpu.
+//!
+//! These are re-exported by wgpu when its `derive` feature is enabled,
+//! and should be used through it.
+
+extern crate proc_macro;
+
+mod shader_type;
+mod vertex;
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+/// Derives `wgpu::VertexLayout` for a `#[repr(C)]` struct.
+///
+/// Every field becomes a vertex attribute, with a format inferred from its type:
+///
+/// - `f32`, `[f32; 2]`, `[f32; 3]` and `[f32; 4]` map to `Float` to `Float4`,
+///   and likewise `u32` to `Uint`s and `i32` to `Int`s.
+/// - `[u8; N]`, `[i8; N]`, `[u16; N]` and `[i16; N]`, with `N` being 2 or 4, map to
+///   `UcharN`, `CharN`, `UshortN` and `ShortN`. With `#[normalized]`, they map to the
+///   `Norm` variants instead.
+///
+/// Other attributes are available on fields:
+///
+/// - `#[format(Half2)]` sets the format explicitly, for types that can't be inferred.
+/// - `#[location(3)]` sets the shader location. Following fields continue from it.
+///   Locations start at 0 otherwise.
+/// - `#[skip]` leaves out a field, such as padding.
+///
+/// `#[instance]` on the struct makes it stepped per instance instead of per vertex.
+#[proc_macro_derive(VertexLayout, attributes(format, instance, location, normalized, skip))]
+pub fn derive_vertex_layout(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    vertex::derive(input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+/// Derives `wgpu::util::ShaderType` for a struct.
+///
+/// Every field must implement `ShaderType`, or be an array of such types.
+/// Arrays of 2 to 4 scalars are vectors, other arrays are laid out as shader arrays.
+#[proc_macro_derive(ShaderType)]
+pub fn derive_shader_type(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    shader_type::derive(input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
@@ -0,0 +1,153 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Error, Expr, Index, Lit, Result, Type};
+
+/// Returns whether the type is a vector, i.e. an array of 2 to 4 scalars.
+fn is_vector(elem: &Type, len: &Expr) -> bool {
+    let scalar = match elem {
+        Type::Path(path) if path.qself.is_none() => match path.path.get_ident() {
+            Some(ident) => ["f32", "i32", "u32", "bool"].contains(&ident.to_string().as_str()),
+            None => false,
+        },
+        _ => false,
+    };
+    let len = match len {
+        Expr::Lit(expr) => match expr.lit {
+            Lit::Int(ref int) => int.base10_parse::<usize>().ok(),
+            _ => None,
+        },
+        _ => None,
+    };
+    scalar && matches!(len, Some(2..=4))
+}
+
+/// Expression of the alignment of a type.
+fn alignment(ty: &Type) -> TokenStream {
+    match ty {
+        Type::Array(array) if !is_vector(&array.elem, &array.len) => {
+            let element_alignment = alignment(&array.elem);
+            quote!(layout.array_alignment(#element_alignment))
+        }
+        ty => quote!(<#ty as ::wgpu::util::ShaderType>::alignment(layout)),
+    }
+}
+
+/// Expression of the size of a type.
+fn size(ty: &Type) -> TokenStream {
+    match ty {
+        Type::Array(array) if !is_vector(&array.elem, &array.len) => {
+            let stride = stride(&array.elem);
+            let len = &array.len;
+            quote!(#stride * (#len))
+        }
+        ty => quote!(<#ty as ::wgpu::util::ShaderType>::size(layout)),
+    }
+}
+
+/// Expression of the stride of an array of a type.
+fn stride(elem: &Type) -> TokenStream {
+    let element_size = size(elem);
+    let element_alignment = alignment(elem);
+    quote!(layout.array_stride(#element_size, #element_alignment))
+}
+
+/// Statement writing `value` of a type at `offset` in `out`.
+fn write(ty: &Type, value: TokenStream, offset: TokenStream, depth: usize) -> TokenStream {
+    match ty {
+        Type::Array(array) if !is_vector(&array.elem, &array.len) => {
+            let stride = stride(&array.elem);
+            let index = format_ident!("index_{}", depth);
+            let element = format_ident!("element_{}", depth);
+            let write_element = write(
+                &array.elem,
+                quote!(#element),
+                quote!(#offset + #index * stride),
+                depth + 1,
+            );
+            quote! {
+                {
+                    let stride = #stride;
+                    for (#index, #element) in (#value).iter().enumerate() {
+                        #write_element
+                    }
+                }
+            }
+        }
+        ty => quote! {
+            <#ty as ::wgpu::util::ShaderType>::write_bytes(#value, layout, &mut out[#offset..]);
+        },
+    }
+}
+
+pub fn derive(input: DeriveInput) -> Result<TokenStream> {
+    let fields = match input.data {
+        Data::Struct(ref data) => &data.fields,
+        _ => {
+            return Err(Error::new(
+                input.ident.span(),
+                "`ShaderType` can only be derived for structs",
+            ))
+        }
+    };
+    if fields.is_empty() {
+        return Err(Error::new(
+            input.ident.span(),
+            "`ShaderType` can't be derived for structs without fields",
+        ));
+    }
+
+    let alignments = fields.iter().map(|field| alignment(&field.ty));
+    let alignments = quote!(0 #(.max(#alignments))*);
+
+    // Each field is placed at the end of the previous one, rounded up to its alignment.
+    let mut layout_fields = Vec::new();
+    let mut write_fields = Vec::new();
+    for (index, field) in fields.iter().enumerate() {
+        let field_alignment = alignment(&field.ty);
+        let field_size = size(&field.ty);
+        let place = quote! {
+            let align = #field_alignment;
+            let offset = (offset + align - 1) / align * align;
+        };
+        let value = match field.ident {
+            Some(ref ident) => quote!(&self.#ident),
+            None => {
+                let index = Index::from(index);
+                quote!(&self.#index)
+            }
+        };
+        let write_field = write(&field.ty, value, quote!(offset), 0);
+        layout_fields.push(quote! {
+            #place
+            let offset = offset + #field_size;
+        });
+        write_fields.push(quote! {
+            #place
+            #write_field
+            let offset = offset + #field_size;
+        });
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::wgpu::util::ShaderType for #name #ty_generics #where_clause {
+            fn alignment(layout: ::wgpu::util::MemoryLayout) -> usize {
+                layout.struct_alignment(#alignments)
+            }
+
+            fn size(layout: ::wgpu::util::MemoryLayout) -> usize {
+                let offset = 0usize;
+                #(#layout_fields)*
+                let align = <Self as ::wgpu::util::ShaderType>::alignment(layout);
+                (offset + align - 1) / align * align
+            }
+
+            #[allow(unused_variables)]
+            fn write_bytes(&self, layout: ::wgpu::util::MemoryLayout, out: &mut [u8]) {
+                let offset = 0usize;
+                #(#write_fields)*
+            }
+        }
+    })
+}
@@ -0,0 +1,258 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{
+    spanned::Spanned, Attribute, Data, DeriveInput, Error, Expr, Field, Ident, Lit, Meta,
+    NestedMeta, Result, Type,
+};
+
+/// Layout-related options of a `#[repr]` attribute.
+#[derive(Default)]
+struct Repr {
+    c: bool,
+    packed: bool,
+}
+
+fn parse_repr(attrs: &[Attribute]) -> Result<Repr> {
+    let mut repr = Repr::default();
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("repr")) {
+        if let Meta::List(list) = attr.parse_meta()? {
+            for nested in list.nested.iter() {
+                match nested {
+                    NestedMeta::Meta(meta) if meta.path().is_ident("C") => repr.c = true,
+                    NestedMeta::Meta(meta) if meta.path().is_ident("packed") => repr.packed = true,
+                    _ => {}
+                }
+            }
+        }
+    }
+    Ok(repr)
+}
+
+fn has_flag(attrs: &[Attribute], name: &str) -> Result<bool> {
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident(name)) {
+        match attr.parse_meta()? {
+            Meta::Path(_) => return Ok(true),
+            meta => {
+                return Err(Error::new(
+                    meta.span(),
+                    format!("expected `#[{}]` without arguments", name),
+                ))
+            }
+        }
+    }
+    Ok(false)
+}
+
+fn parse_location(attrs: &[Attribute]) -> Result<Option<u32>> {
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("location")) {
+        let lit = match attr.parse_meta()? {
+            Meta::List(ref list) if list.nested.len() == 1 => match list.nested[0] {
+                NestedMeta::Lit(ref lit) => lit.clone(),
+                ref other => return Err(Error::new(other.span(), "expected an integer")),
+            },
+            Meta::NameValue(name_value) => name_value.lit,
+            meta => {
+                return Err(Error::new(
+                    meta.span(),
+                    "expected `#[location(N)]` or `#[location = N]`",
+                ))
+            }
+        };
+        return match lit {
+            Lit::Int(int) => int.base10_parse().map(Some),
+            other => Err(Error::new(other.span(), "expected an integer")),
+        };
+    }
+    Ok(None)
+}
+
+fn parse_format(attrs: &[Attribute]) -> Result<Option<Ident>> {
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("format")) {
+        return match attr.parse_meta()? {
+            Meta::List(ref list) if list.nested.len() == 1 => match list.nested[0] {
+                NestedMeta::Meta(Meta::Path(ref path)) if path.get_ident().is_some() => {
+                    Ok(path.get_ident().cloned())
+                }
+                ref other => Err(Error::new(
+                    other.span(),
+                    "expected the name of a `VertexFormat` variant",
+                )),
+            },
+            meta => Err(Error::new(meta.span(), "expected `#[format(Variant)]`")),
+        };
+    }
+    Ok(None)
+}
+
+fn scalar_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(path) if path.qself.is_none() => {
+            path.path.get_ident().map(|ident| ident.to_string())
+        }
+        _ => None,
+    }
+}
+
+fn array_len(len: &Expr) -> Option<usize> {
+    match len {
+        Expr::Lit(expr) => match expr.lit {
+            Lit::Int(ref int) => int.base10_parse().ok(),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Infers the vertex format of a field from its type.
+fn infer_format(field: &Field, normalized: bool) -> Result<Ident> {
+    let (scalar, count) = match field.ty {
+        Type::Array(ref array) => (scalar_name(&array.elem), array_len(&array.len)),
+        ref ty => (scalar_name(ty), Some(1)),
+    };
+    let (scalar, count) = match (scalar, count) {
+        (Some(scalar), Some(count)) => (scalar, count),
+        _ => {
+            return Err(Error::new(
+                field.ty.span(),
+                "unable to infer the vertex format of this type, use `#[format(...)]`",
+            ))
+        }
+    };
+
+    let name = match (scalar.as_str(), count, normalized) {
+        ("f32", 1, false) => "Float",
+        ("f32", 2, false) => "Float2",
+        ("f32", 3, false) => "Float3",
+        ("f32", 4, false) => "Float4",
+        ("u32", 1, false) => "Uint",
+        ("u32", 2, false) => "Uint2",
+        ("u32", 3, false) => "Uint3",
+        ("u32", 4, false) => "Uint4",
+        ("i32", 1, false) => "Int",
+        ("i32", 2, false) => "Int2",
+        ("i32", 3, false) => "Int3",
+        ("i32", 4, false) => "Int4",
+        ("u8", 2, false) => "Uchar2",
+        ("u8", 4, false) => "Uchar4",
+        ("u8", 2, true) => "Uchar2Norm",
+        ("u8", 4, true) => "Uchar4Norm",
+        ("i8", 2, false) => "Char2",
+        ("i8", 4, false) => "Char4",
+        ("i8", 2, true) => "Char2Norm",
+        ("i8", 4, true) => "Char4Norm",
+        ("u16", 2, false) => "Ushort2",
+        ("u16", 4, false) => "Ushort4",
+        ("u16", 2, true) => "Ushort2Norm",
+        ("u16", 4, true) => "Ushort4Norm",
+        ("i16", 2, false) => "Short2",
+        ("i16", 4, false) => "Short4",
+        ("i16", 2, true) => "Short2Norm",
+        ("i16", 4, true) => "Short4Norm",
+        (_, _, true) => {
+            return Err(Error::new(
+                field.ty.span(),
+                "`#[normalized]` requires an array of 2 or 4 `u8`, `i8`, `u16` or `i16`",
+            ))
+        }
+        _ => {
+            return Err(Error::new(
+                field.ty.span(),
+                "unable to infer the vertex format of this type, use `#[format(...)]`",
+            ))
+        }
+    };
+    Ok(Ident::new(name, field.ty.span()))
+}
+
+pub fn derive(input: DeriveInput) -> Result<TokenStream> {
+    let fields = match input.data {
+        Data::Struct(ref data) => &data.fields,
+        _ => {
+            return Err(Error::new(
+                input.ident.span(),
+                "`VertexLayout` can only be derived for structs",
+            ))
+        }
+    };
+    let repr = parse_repr(&input.attrs)?;
+    if !repr.c {
+        return Err(Error::new(
+            input.ident.span(),
+            "`VertexLayout` requires `#[repr(C)]`, so that field offsets are well defined",
+        ));
+    }
+    let step_mode = if has_flag(&input.attrs, "instance")? {
+        quote!(Instance)
+    } else {
+        quote!(Vertex)
+    };
+
+    let mut offsets = Vec::new();
+    let mut attributes = Vec::new();
+    let mut location = 0u32;
+    let mut previous: Option<(Ident, &Type)> = None;
+    for (index, field) in fields.iter().enumerate() {
+        // Fields of a `#[repr(C)]` struct are laid out in order, each one aligned
+        // to its type, unless the struct is packed.
+        let offset = format_ident!("offset_{}", index);
+        let ty = &field.ty;
+        let align = if repr.packed {
+            quote!(1)
+        } else {
+            quote!(::std::mem::align_of::<#ty>() as ::wgpu::BufferAddress)
+        };
+        offsets.push(match previous {
+            Some((ref previous_offset, previous_ty)) => quote! {
+                let #offset = {
+                    let end = #previous_offset
+                        + ::std::mem::size_of::<#previous_ty>() as ::wgpu::BufferAddress;
+                    let align = #align;
+                    (end + align - 1) / align * align
+                };
+            },
+            None => quote! {
+                let #offset: ::wgpu::BufferAddress = 0;
+            },
+        });
+        previous = Some((offset.clone(), ty));
+
+        if has_flag(&field.attrs, "skip")? {
+            continue;
+        }
+        if let Some(explicit) = parse_location(&field.attrs)? {
+            location = explicit;
+        }
+        let normalized = has_flag(&field.attrs, "normalized")?;
+        let format = match parse_format(&field.attrs)? {
+            Some(format) if normalized => {
+                return Err(Error::new(
+                    format.span(),
+                    "`#[normalized]` can't be combined with `#[format(...)]`",
+                ))
+            }
+            Some(format) => format,
+            None => infer_format(field, normalized)?,
+        };
+        attributes.push(quote! {
+            ::wgpu::VertexAttributeDescriptor {
+                offset: #offset,
+                format: ::wgpu::VertexFormat::#format,
+                shader_location: #location,
+            }
+        });
+        location += 1;
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::wgpu::VertexLayout for #name #ty_generics #where_clause {
+            const STEP_MODE: ::wgpu::InputStepMode = ::wgpu::InputStepMode::#step_mode;
+            #[allow(unused_variables)]
+            const ATTRIBUTES: &'static [::wgpu::VertexAttributeDescriptor] = {
+                #(#offsets)*
+                &[#(#attributes),*]
+            };
+        }
+    })
+}